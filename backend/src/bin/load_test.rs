@@ -0,0 +1,192 @@
+//! Load test simulating many concurrent lobbies against a running server
+//!
+//! Each lobby logs in five players, creates and fills a game, then has every player poll the game and its messages
+//! before and after the host starts it. Latencies of all the requests are reported at the end.
+//!
+//! Usage: `cargo run --release --bin load_test -- [base_url] [lobbies] [rounds]`, defaulting to
+//! `http://localhost:8000/api 200 10`
+
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, Instant},
+};
+
+use reqwest::{Client, Method};
+use serde_json::{json, Value};
+use tokio::task::JoinSet;
+use uuid::Uuid;
+
+const PLAYERS_PER_LOBBY: usize = 5;
+
+#[derive(Default)]
+struct Stats {
+    latencies: Mutex<Vec<Duration>>,
+    errors: AtomicUsize,
+}
+
+struct Context {
+    client: Client,
+    base_url: String,
+    /// Prefix of the player names, so that several runs do not collide
+    run: String,
+    rounds: usize,
+    stats: Stats,
+}
+
+impl Context {
+    /// Sends a request, recording its latency, and returns its JSON body if it succeeded
+    async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        token: Option<&str>,
+        body: Option<Value>,
+    ) -> Option<Value> {
+        let mut builder = self
+            .client
+            .request(method, format!("{}{}", self.base_url, path));
+        if let Some(token) = token {
+            builder = builder.bearer_auth(token);
+        }
+        if let Some(body) = body {
+            builder = builder.json(&body);
+        }
+
+        let start = Instant::now();
+        let response = builder.send().await;
+        self.stats.latencies.lock().unwrap().push(start.elapsed());
+
+        match response {
+            Ok(response) if response.status().is_success() => {
+                Some(response.json().await.unwrap_or(Value::Null))
+            }
+            Ok(response) => {
+                self.stats.errors.fetch_add(1, Ordering::Relaxed);
+                eprintln!("{path}: {}", response.status());
+                None
+            }
+            Err(e) => {
+                self.stats.errors.fetch_add(1, Ordering::Relaxed);
+                eprintln!("{path}: {e}");
+                None
+            }
+        }
+    }
+
+    /// Has every player fetch the game and their messages
+    async fn poll(self: &Arc<Self>, tokens: &[String]) {
+        let mut tasks = JoinSet::new();
+
+        for token in tokens {
+            let (ctx, token) = (self.clone(), token.clone());
+            tasks.spawn(async move {
+                for _ in 0..ctx.rounds {
+                    ctx.request(Method::GET, "/game", Some(&token), None).await;
+                    ctx.request(Method::GET, "/updates", Some(&token), None)
+                        .await;
+                }
+            });
+        }
+
+        while tasks.join_next().await.is_some() {}
+    }
+
+    async fn run_lobby(self: Arc<Self>, lobby: usize) {
+        let mut tokens = Vec::with_capacity(PLAYERS_PER_LOBBY);
+
+        for i in 0..PLAYERS_PER_LOBBY {
+            let form = json!({
+                "name": format!("{}{lobby:04}{i}", self.run),
+                "secret": Uuid::new_v4().to_string(),
+            });
+            let Some(token) = self
+                .request(Method::POST, "/login", None, Some(form))
+                .await
+                .and_then(|r| r["token"].as_str().map(str::to_owned))
+            else {
+                return;
+            };
+            tokens.push(token);
+        }
+
+        let Some(uid) = self
+            .request(Method::POST, "/game", Some(&tokens[0]), None)
+            .await
+            .and_then(|r| r.as_str().map(str::to_owned))
+        else {
+            return;
+        };
+
+        for token in tokens.iter().skip(1) {
+            self.request(
+                Method::POST,
+                &format!("/game/{uid}/join"),
+                Some(token),
+                None,
+            )
+            .await;
+        }
+
+        self.poll(&tokens).await;
+        self.request(Method::POST, "/game/start", Some(&tokens[0]), None)
+            .await;
+        self.poll(&tokens).await;
+    }
+}
+
+#[tokio::main]
+async fn main() {
+    let mut args = std::env::args().skip(1);
+    let base_url = args
+        .next()
+        .unwrap_or_else(|| "http://localhost:8000/api".into());
+    let lobbies = args.next().and_then(|a| a.parse().ok()).unwrap_or(200);
+    let rounds = args.next().and_then(|a| a.parse().ok()).unwrap_or(10);
+
+    let ctx = Arc::new(Context {
+        client: Client::new(),
+        base_url,
+        run: Uuid::new_v4().simple().to_string()[..4].to_owned(),
+        rounds,
+        stats: Stats::default(),
+    });
+
+    println!(
+        "Simulating {lobbies} lobbies of {PLAYERS_PER_LOBBY} players, {rounds} polling rounds each"
+    );
+
+    let start = Instant::now();
+    let mut tasks = JoinSet::new();
+    for lobby in 0..lobbies {
+        tasks.spawn(ctx.clone().run_lobby(lobby));
+    }
+    while tasks.join_next().await.is_some() {}
+    let elapsed = start.elapsed();
+
+    let mut latencies = ctx.stats.latencies.lock().unwrap().clone();
+    latencies.sort();
+    let percentile = |p: usize| {
+        latencies
+            .get((latencies.len() * p / 100).min(latencies.len().saturating_sub(1)))
+            .copied()
+            .unwrap_or_default()
+    };
+
+    println!(
+        "{} requests in {:.2?} ({:.0} req/s), {} errors",
+        latencies.len(),
+        elapsed,
+        latencies.len() as f64 / elapsed.as_secs_f64(),
+        ctx.stats.errors.load(Ordering::Relaxed)
+    );
+    println!(
+        "latency p50 {:.2?}, p95 {:.2?}, p99 {:.2?}, max {:.2?}",
+        percentile(50),
+        percentile(95),
+        percentile(99),
+        latencies.last().copied().unwrap_or_default()
+    );
+}
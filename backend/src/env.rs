@@ -4,15 +4,71 @@ use envconfig::Envconfig;
 
 #[derive(Envconfig)]
 pub struct Config {
-    /* #[envconfig(from = "LOL_API_KEY")]
-    pub lol_api_key: String,
+    /// Key for the Riot API. Features relying on it are disabled when missing
+    #[envconfig(from = "LOL_API_KEY")]
+    pub lol_api_key: Option<String>,
     #[envconfig(from = "LOL_API_REGION", default = "europe")]
     pub lol_api_region: String,
     #[envconfig(from = "LOL_API_SERVER", default = "euw1")]
-    pub lol_api_server: String, */
+    pub lol_api_server: String,
 
+    /// Legacy HMAC secret, verifying the tokens issued without key id
     #[envconfig(from = "JWT_SECRET")]
-    pub jwt_secret: String,
+    pub jwt_secret: Option<String>,
+    /// HMAC secrets identified by a key id, formatted as `kid=secret,kid=secret`
+    #[envconfig(from = "JWT_KEYS", default = "")]
+    pub jwt_keys: String,
+    /// EdDSA or RS256 keys loaded from PEM files, formatted as `kid=ALG:public.pem[:private.pem],...`
+    #[envconfig(from = "JWT_KEY_FILES", default = "")]
+    pub jwt_key_files: String,
+    /// Id of the key signing new tokens, defaults to the last configured key having a private part
+    #[envconfig(from = "JWT_SIGNING_KEY")]
+    pub jwt_signing_key: Option<String>,
+    /// Lifetime of access tokens, in seconds
+    #[envconfig(from = "ACCESS_TOKEN_DURATION", default = "900")]
+    pub access_token_duration: u64,
+    /// Lifetime of refresh tokens, in seconds
+    #[envconfig(from = "REFRESH_TOKEN_DURATION", default = "2592000")]
+    pub refresh_token_duration: u64,
+    /// Lifetime of a login session, in seconds, after which refreshing its tokens requires to log in again
+    #[envconfig(from = "SESSION_MAX_DURATION", default = "2592000")]
+    pub session_max_duration: u64,
+
+    /// Whether session cookies are only sent over HTTPS
+    #[envconfig(from = "SECURE_COOKIES", default = "true")]
+    pub secure_cookies: bool,
+
+    /// Inactivity after which a reserved name can be claimed by someone else, in seconds
+    #[envconfig(from = "NAME_RESERVATION_TIMEOUT", default = "604800")]
+    pub name_reservation_timeout: u64,
+
+    /// Inactivity after which a lobby that was never started is deleted, in seconds
+    #[envconfig(from = "LOBBY_IDLE_TIMEOUT", default = "3600")]
+    pub lobby_idle_timeout: u64,
+    /// Time during which a finished game stays available to its players, in seconds
+    #[envconfig(from = "FINISHED_GAME_RETENTION", default = "3600")]
+    pub finished_game_retention: u64,
+    /// Inactivity after which a game in progress is considered abandoned and deleted, in seconds
+    #[envconfig(from = "ABANDONED_GAME_TIMEOUT", default = "14400")]
+    pub abandoned_game_timeout: u64,
+    /// Inactivity after which the messages of a player outside of any game are dropped, in seconds
+    #[envconfig(from = "PROXY_IDLE_TIMEOUT", default = "3600")]
+    pub proxy_idle_timeout: u64,
+
+    /// Time given to the players to vote once the game is over, in seconds
+    #[envconfig(from = "VOTE_TIMEOUT", default = "300")]
+    pub vote_timeout: u64,
+
+    /// Token granting access to the admin endpoints, through the X-Admin-Token header. They are disabled when missing
+    #[envconfig(from = "ADMIN_TOKEN")]
+    pub admin_token: Option<String>,
+
+    /// Format of the logs, either `pretty` or `json`
+    #[envconfig(from = "LOG_FORMAT", default = "pretty")]
+    pub log_format: String,
+    /// Filter of the logs, as accepted by tracing-subscriber's EnvFilter (e.g. `info,backend=debug`)
+    #[envconfig(from = "LOG_LEVEL", default = "info")]
+    pub log_level: String,
 
     #[envconfig(from = "BASE_URI", default = "/api")]
     pub uri: String,
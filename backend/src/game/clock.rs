@@ -0,0 +1,207 @@
+//! Time elapsed in the League game
+//!
+//! The timed events of the classes are expressed in game time, measured by a [GameClock]. It follows either the real
+//! time, the time reported by the LoL clients of the players, or a time set by hand to test the events without
+//! waiting for them.
+//!
+//! The game time reported by the clients is authoritative: it stops during pauses and loading screens, which delays
+//! the timed events accordingly. Between two uploads, it is extrapolated from the real time. The clock never goes
+//! back, so a pause detected late or an upload lagging behind the others only holds it until the reported time
+//! catches up.
+
+use std::time::{Duration, Instant};
+
+use serde::{Deserialize, Serialize};
+
+use crate::routes::error::Error;
+
+/// Time after which a game time that did not advance is considered paused
+const PAUSE_DETECTION: Duration = Duration::from_secs(2);
+/// Advance of the reported game time, in seconds, below which it is considered to stand still
+///
+/// The clients of the players stop at slightly different times during a pause, so their uploads alternate between
+/// close values.
+const PAUSE_TOLERANCE: f64 = 0.5;
+/// Longest extrapolation of the reported game time, so that the clock stops when the clients stop uploading
+const MAX_EXTRAPOLATION: Duration = Duration::from_secs(30);
+
+/// Source of the game time
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ClockMode {
+    /// Real time until live data is received, then the game time it reports
+    #[default]
+    Auto,
+    /// Real time elapsed since the start of the game
+    Real,
+    /// Game time reported by the live data of the players
+    Live,
+    /// Game time only changing when set through the admin API
+    Manual,
+}
+
+#[derive(Debug, Clone)]
+pub enum GameClock {
+    Real {
+        /// Instant at which the game time was zero
+        start: Instant,
+    },
+    Live {
+        /// Furthest game time received, in seconds
+        game_time: f64,
+        /// Instant at which `game_time` was received
+        received: Instant,
+        /// Whether the game time stopped advancing
+        paused: bool,
+        /// Game time at the last observation, in seconds, below which the clock never goes
+        floor: u64,
+    },
+    Manual {
+        /// Current game time, in seconds
+        game_time: u64,
+    },
+}
+
+impl GameClock {
+    /// Creates a clock whose game time is currently `elapsed`
+    pub fn new(mode: ClockMode, elapsed: Duration) -> Self {
+        match mode {
+            ClockMode::Auto | ClockMode::Real => GameClock::Real {
+                start: Instant::now()
+                    .checked_sub(elapsed)
+                    .unwrap_or_else(Instant::now),
+            },
+            ClockMode::Live => GameClock::Live {
+                game_time: elapsed.as_secs_f64(),
+                received: Instant::now(),
+                paused: false,
+                floor: elapsed.as_secs(),
+            },
+            ClockMode::Manual => GameClock::Manual {
+                game_time: elapsed.as_secs(),
+            },
+        }
+    }
+
+    pub fn mode(&self) -> ClockMode {
+        match self {
+            GameClock::Real { .. } => ClockMode::Real,
+            GameClock::Live { .. } => ClockMode::Live,
+            GameClock::Manual { .. } => ClockMode::Manual,
+        }
+    }
+
+    /// Current game time, in seconds
+    pub fn now(&self) -> u64 {
+        match *self {
+            GameClock::Real { start } => start.elapsed().as_secs(),
+            GameClock::Live {
+                game_time,
+                received,
+                paused,
+                floor,
+            } => {
+                let extrapolated = if paused {
+                    Duration::ZERO
+                } else {
+                    received.elapsed().min(MAX_EXTRAPOLATION)
+                };
+                ((game_time.max(0.0) + extrapolated.as_secs_f64()) as u64).max(floor)
+            }
+            GameClock::Manual { game_time } => game_time,
+        }
+    }
+
+    /// Whether the game time is currently stopped by a pause of the League game
+    pub fn is_paused(&self) -> bool {
+        matches!(self, GameClock::Live { paused: true, .. })
+    }
+
+    /// Returns the instant at which the game time will reach `game_time`
+    ///
+    /// Returns `None` if it cannot be predicted, because the clock is paused, would have to be extrapolated for too
+    /// long, or does not advance by itself. The deadline is then processed once the clock is updated.
+    pub fn instant_of(&self, game_time: u64) -> Option<Instant> {
+        if game_time <= self.now() {
+            return Some(Instant::now());
+        }
+
+        match *self {
+            GameClock::Real { start } => Some(start + Duration::from_secs(game_time)),
+            GameClock::Live {
+                game_time: reported,
+                received,
+                paused: false,
+                ..
+            } => Some(received + Duration::from_secs_f64(game_time as f64 - reported.max(0.0)))
+                .filter(|at| *at <= received + MAX_EXTRAPOLATION),
+            GameClock::Live { .. } | GameClock::Manual { .. } => None,
+        }
+    }
+
+    /// Converts the clock to another mode, keeping its current game time
+    pub fn with_mode(&self, mode: ClockMode) -> Self {
+        match (mode, self.mode()) {
+            (ClockMode::Auto, ClockMode::Real | ClockMode::Live) => self.clone(),
+            (mode, current) if mode == current => self.clone(),
+            (mode, _) => Self::new(mode, Duration::from_secs(self.now())),
+        }
+    }
+
+    /// Records the game time reported by the live data
+    ///
+    /// - mode: Mode of the clock of the game, an automatic clock starts following the reported time
+    pub fn observe(&mut self, mode: ClockMode, reported: f64) {
+        let current = self.now();
+
+        if mode == ClockMode::Auto && self.mode() == ClockMode::Real {
+            tracing::info!(
+                game_time = reported,
+                "Following the game time of the live data"
+            );
+            *self = GameClock::Live {
+                game_time: reported,
+                received: Instant::now(),
+                paused: false,
+                floor: current,
+            };
+            return;
+        }
+
+        let GameClock::Live {
+            ref mut game_time,
+            ref mut received,
+            ref mut paused,
+            ref mut floor,
+        } = *self
+        else {
+            return;
+        };
+        *floor = current;
+
+        // Only an advance over every previous upload counts, so that lagging clients do not hide a pause
+        if reported > *game_time + PAUSE_TOLERANCE {
+            if *paused {
+                tracing::info!(game_time = reported, "League game resumed");
+            }
+            *game_time = reported;
+            *received = Instant::now();
+            *paused = false;
+        } else if !*paused && received.elapsed() >= PAUSE_DETECTION {
+            // The reception instant is kept while the time does not advance, so that repeated uploads are detected
+            tracing::info!(game_time = reported, "League game paused");
+            *paused = true;
+        }
+    }
+
+    /// Jumps to the given game time, only allowed for manual clocks
+    pub fn set(&mut self, to: u64) -> Result<(), Error> {
+        match *self {
+            GameClock::Manual { ref mut game_time } => {
+                *game_time = to;
+                Ok(())
+            }
+            _ => Err(Error::IncorrectState),
+        }
+    }
+}
@@ -0,0 +1,77 @@
+use serde::Serialize;
+
+use crate::lol_api::matches::MatchDto;
+
+use super::player::Player;
+
+/// Authoritative result of a finished League game, fetched from match-v5
+#[derive(Debug, Clone, Serialize)]
+pub struct MatchResult {
+    pub match_id: String,
+    /// Duration of the game, in seconds
+    pub duration: i64,
+    pub participants: Vec<ParticipantResult>,
+}
+
+/// Statistics of one participant of a finished game
+#[derive(Debug, Clone, Serialize)]
+pub struct ParticipantResult {
+    #[serde(skip)]
+    pub puuid: String,
+    pub summoner_name: String,
+    pub champion_name: String,
+    pub team_id: i64,
+    pub position: String,
+    pub kills: i64,
+    pub deaths: i64,
+    pub assists: i64,
+    /// Damage dealt to enemy champions
+    pub damage: i64,
+    pub win: bool,
+}
+
+impl From<MatchDto> for MatchResult {
+    fn from(value: MatchDto) -> Self {
+        Self {
+            match_id: value.metadata.match_id,
+            duration: value.info.game_duration,
+            participants: value
+                .info
+                .participants
+                .into_iter()
+                .map(|p| ParticipantResult {
+                    puuid: p.puuid,
+                    summoner_name: p.summoner_name,
+                    champion_name: p.champion_name,
+                    team_id: p.team_id,
+                    position: p.team_position,
+                    kills: p.kills,
+                    deaths: p.deaths,
+                    assists: p.assists,
+                    damage: p.total_damage_dealt_to_champions,
+                    win: p.win,
+                })
+                .collect(),
+        }
+    }
+}
+
+impl MatchResult {
+    /// Returns the participant played by the given player, if it can be identified
+    pub fn participant(&self, player: &Player) -> Option<&ParticipantResult> {
+        let account = player.session.account.as_ref();
+        let summoner_name = player.summoner_name();
+
+        self.participants.iter().find(|p| {
+            account.is_some_and(|a| a.puuid == p.puuid)
+                || summoner_name.is_some_and(|n| n == p.summoner_name)
+        })
+    }
+
+    /// Returns all the participants of the given team
+    pub fn team(&self, team_id: i64) -> impl Iterator<Item = &ParticipantResult> {
+        self.participants
+            .iter()
+            .filter(move |p| p.team_id == team_id)
+    }
+}
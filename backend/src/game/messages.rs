@@ -1,6 +1,6 @@
 use serde::Serialize;
 
-use super::{player::classes::{droid::Mission, romeo::Juliette}, team_builder::Role, PublicInnerState};
+use super::{player::classes::{droid::Mission, kamikaze::KamikazeState, romeo::Juliette}, team_builder::Role, PublicInnerState};
 
 /// Messages that can be sent to players through the [PlayerProxy] interface
 #[derive(Serialize, Debug, Clone)]
@@ -23,6 +23,10 @@ pub enum Message {
     TwoFaceState {
         inting: bool,
     },
+    KamikazeRank {
+        #[serde(flatten)]
+        rank: KamikazeState,
+    },
     VotesCompleted,
     State {
         state: PublicInnerState,
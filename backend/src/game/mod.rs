@@ -1,22 +1,77 @@
 use self::{
-    player::{classes::PlayerState, proxy::PlayerProxy, Player},
+    clock::{ClockMode, GameClock},
+    match_result::MatchResult,
+    player::{
+        bot::{self, Bot, VoteStrategy},
+        classes::PlayerState,
+        proxy::PlayerProxy,
+        Player, SummonerBinding,
+    },
     team_builder::Role,
 };
-use crate::{routes::error::Error, session_management::UserSession};
+use crate::{
+    env::env_config,
+    lol_api::{
+        matches,
+        spectator::{self, CurrentGameInfo},
+    },
+    models::{EventData, GameResult, MergedGameData, Team},
+    routes::error::Error,
+    scheduler::Scheduler,
+    session_management::UserSession,
+    state::PlayerIndex,
+};
 use mutable::Mutable;
-use serde::Serialize;
+use rand::{seq::SliceRandom, SeedableRng};
+use rand_chacha::ChaCha8Rng;
+use serde::{Deserialize, Serialize};
 use std::{
-    collections::{hash_map, HashMap},
-    sync::{Arc, Weak},
-    time::{Duration, Instant},
+    collections::HashMap,
+    sync::Arc,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
 };
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+/// Random number generator of a game, seeded so that a session can be reproduced
+pub type GameRng = ChaCha8Rng;
+
+pub mod clock;
+pub mod match_result;
 pub mod messages;
 pub mod player;
 pub mod team_builder;
 
+/// Interval between two lookups of the players' game through the spectator API
+const SPECTATOR_POLL_INTERVAL: Duration = Duration::from_secs(30);
+/// Interval between two lookups of the finished match through the match-v5 API
+const MATCH_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Smallest lobby which can be started
+pub const MIN_PLAYERS: usize = 2;
+/// Largest lobby, a whole League team, or largest team of a [LobbyMode::Versus] lobby
+pub const MAX_PLAYERS: usize = 5;
+
+/// Teams of the League game played by the lobby
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LobbyMode {
+    /// All the players are in the same team
+    #[default]
+    Single,
+    /// The players are split between both teams, each with its own composition
+    Versus,
+}
+
+/// Rules of a lobby, chosen when it is created
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LobbyOptions {
+    pub mode: LobbyMode,
+    /// Whether the players of a [LobbyMode::Versus] lobby may also guess the roles of their opponents
+    pub cross_team_guesses: bool,
+}
+
 macro_rules! require_state {
     ($state:pat, $self:expr) => {
         let $state = $self.state else { return Err(Error::IncorrectState); };
@@ -28,6 +83,13 @@ macro_rules! require_state {
 pub struct GameStatus {
     uid: Uuid,
     player_names: Vec<String>,
+    /// Players controlled by the server
+    bot_names: Vec<String>,
+    host: Option<String>,
+    #[serde(flatten)]
+    options: LobbyOptions,
+    /// Side of each player, in versus lobbies
+    teams: HashMap<String, Team>,
     #[serde(flatten)]
     state: PublicInnerState,
 }
@@ -37,22 +99,135 @@ pub struct GameStatus {
 pub struct AuthenticatedGameStatus {
     uid: Uuid,
     player_names: Vec<String>,
+    /// Players controlled by the server
+    bot_names: Vec<String>,
+    host: Option<String>,
+    #[serde(flatten)]
+    options: LobbyOptions,
+    /// Side of each player, in versus lobbies
+    teams: HashMap<String, Team>,
     #[serde(skip_serializing_if = "Option::is_none")]
     player_state: Option<PlayerState>,
+    /// Summoners played by each player, once identified
+    summoners: HashMap<String, SummonerBinding>,
     #[serde(flatten)]
     state: PublicInnerState,
 }
 
+/// Status of a game, as seen by the operators of the server
+#[derive(Debug, Serialize)]
+pub struct AdminGameStatus {
+    uid: Uuid,
+    seed: u64,
+    host: Option<String>,
+    #[serde(flatten)]
+    options: LobbyOptions,
+    state: StateKind,
+    players: Vec<AdminPlayerStatus>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    active_game: Option<ActiveGame>,
+    has_match_result: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    game_result: Option<GameResult>,
+    clock_mode: ClockMode,
+    /// Current game time, in seconds, while in game
+    #[serde(skip_serializing_if = "Option::is_none")]
+    game_time: Option<u64>,
+    /// Whether the League game is paused, while in game
+    #[serde(skip_serializing_if = "Option::is_none")]
+    game_paused: Option<bool>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct AdminPlayerStatus {
+    name: String,
+    role: Option<Role>,
+    binding: Option<SummonerBinding>,
+    /// Number of messages waiting to be fetched by the player
+    pending_messages: usize,
+    /// Strategy of the player, if controlled by the server
+    #[serde(skip_serializing_if = "Option::is_none")]
+    bot: Option<VoteStrategy>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    team: Option<Team>,
+}
+
+/// State of a game, without its data
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum StateKind {
+    Setup,
+    Draft,
+    InGame,
+    Voting,
+    End,
+}
+
+impl StateKind {
+    pub const ALL: [StateKind; 5] = [
+        StateKind::Setup,
+        StateKind::Draft,
+        StateKind::InGame,
+        StateKind::Voting,
+        StateKind::End,
+    ];
+
+    pub fn as_str(self) -> &'static str {
+        match self {
+            StateKind::Setup => "setup",
+            StateKind::Draft => "draft",
+            StateKind::InGame => "in_game",
+            StateKind::Voting => "voting",
+            StateKind::End => "end",
+        }
+    }
+}
+
 /// State of a game
 pub struct GameState {
     uid: Uuid,
     players: HashMap<String, Player>,
+    /// Player managing the lobby
+    host: Option<String>,
     state: InnerState,
+    active_game: Option<ActiveGame>,
+    match_result: Option<MatchResult>,
+    /// Result of the League game reported by the live data, for the team of the players
+    game_result: Option<GameResult>,
+    /// Team which won the League game, according to the live data
+    winning_team: Option<Team>,
+    mode: LobbyMode,
+    /// Whether ballots may name players of the other team
+    cross_team_guesses: bool,
+    /// Last action of the players or change of state, used to collect abandoned games
+    last_activity: Instant,
+    last_spectator_poll: Option<Instant>,
+    last_match_poll: Option<Instant>,
+    index: PlayerIndex,
+    scheduler: Scheduler,
+    /// Set once the game is removed from the global state, so that no player can join it anymore
+    closed: bool,
+    /// Seed of `rng`, reproducing the roles and timings of the game when reused
+    seed: u64,
+    rng: GameRng,
+    /// Mode of the clock created when the League game starts
+    clock_mode: ClockMode,
+}
+
+/// League game in which the players have been detected through the spectator API
+#[derive(Debug, Clone, Serialize)]
+pub struct ActiveGame {
+    pub game_id: i64,
+    pub platform_id: String,
+    /// Start of the game, in epoch milliseconds
+    pub game_start_time: i64,
 }
 
-#[derive(Debug, Mutable, Default)]
+#[derive(Debug, Mutable, Default, Clone)]
 pub struct GameInfo {
     pub duration: u64,
+    /// Latest data uploaded from the players' LoL clients
+    pub live: Option<MergedGameData>,
 }
 
 #[derive(Debug)]
@@ -60,11 +235,13 @@ enum InnerState {
     Setup,
     Draft,
     InGame {
-        start: Instant,
+        clock: GameClock,
         data: Option<GameInfo>,
     },
     Voting {
         votes: HashMap<String, HashMap<String, Role>>,
+        /// Votes are closed at this instant, even if some players did not vote
+        deadline: Instant,
     },
     End {
         votes: HashMap<String, HashMap<String, Role>>,
@@ -83,11 +260,47 @@ pub enum PublicInnerState {
     End {
         votes: HashMap<String, HashMap<String, Role>>,
         roles: HashMap<String, Role>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        result: Option<MatchResult>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        game_result: Option<GameResult>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        winning_team: Option<Team>,
+        /// Whether each player fulfilled their objective, when it could be judged
+        objectives: HashMap<String, bool>,
     },
 }
 
+impl InnerState {
+    fn kind(&self) -> StateKind {
+        match self {
+            InnerState::Setup => StateKind::Setup,
+            InnerState::Draft => StateKind::Draft,
+            InnerState::InGame { .. } => StateKind::InGame,
+            InnerState::Voting { .. } => StateKind::Voting,
+            InnerState::End { .. } => StateKind::End,
+        }
+    }
+
+    /// Returns the latest live data of the game, if any
+    fn live_data(&self) -> Option<&MergedGameData> {
+        match self {
+            InnerState::InGame {
+                data: Some(info), ..
+            } => info.live.as_ref(),
+            _ => None,
+        }
+    }
+}
+
 impl PublicInnerState {
-    fn try_from(value: &InnerState, players: &HashMap<String, Player>) -> Result<Self, Error> {
+    fn try_from(
+        value: &InnerState,
+        players: &HashMap<String, Player>,
+        result: Option<&MatchResult>,
+        game_result: Option<&GameResult>,
+        winning_team: Option<Team>,
+    ) -> Result<Self, Error> {
         fn convert_players(map: &HashMap<String, Player>) -> Vec<String> {
             map.values().map(|p| p.session.name.clone()).collect()
         }
@@ -95,43 +308,65 @@ impl PublicInnerState {
             InnerState::Setup => Self::Setup,
             InnerState::Draft => Self::Draft,
             InnerState::InGame { .. } => Self::InGame,
-            InnerState::Voting { votes } => Self::Voting {
+            InnerState::Voting { votes, .. } => Self::Voting {
                 votes_received: votes.keys().cloned().collect(),
             },
             InnerState::End { votes } => Self::End {
                 votes: votes.clone(),
                 roles: players
                     .values()
-                    .map(|p| (p.session.name.clone(), p.role()))
-                    .fold(Some(HashMap::new()), |map, (name, role)| {
-                        match (map, role) {
-                            (Some(mut map), Some(role)) => {
-                                map.insert(name, role);
-                                Some(map)
-                            }
-                            _ => None,
-                        }
-                    })
+                    .map(|p| p.role().map(|role| (p.session.name.clone(), role)))
+                    .collect::<Option<HashMap<_, _>>>()
                     .ok_or(Error::Internal {
                         msg: "missing role".into(),
                     })?,
+                result: result.cloned(),
+                game_result: game_result.cloned(),
+                winning_team,
+                objectives: result
+                    .map(|result| {
+                        players
+                            .values()
+                            .filter_map(|p| Some((p.session.name.clone(), p.objective(result)?)))
+                            .collect()
+                    })
+                    .unwrap_or_default(),
             },
         })
     }
 }
 
 impl GameState {
-    pub fn new(uid: Uuid) -> Arc<RwLock<Self>> {
-        let state = Arc::new(RwLock::new(Self {
+    pub fn new(
+        uid: Uuid,
+        seed: u64,
+        options: LobbyOptions,
+        index: PlayerIndex,
+        scheduler: Scheduler,
+    ) -> Arc<RwLock<Self>> {
+        tracing::info!(game = %uid, seed, ?options, "Creating game");
+
+        Arc::new(RwLock::new(Self {
             uid,
             players: Default::default(),
+            host: None,
             state: InnerState::Setup {},
-        }));
-
-        // tokio::spawn(Self::listen_events(rx, state.clone()));
-        tokio::spawn(Self::fetch_updates(Arc::downgrade(&state)));
-
-        state
+            active_game: None,
+            match_result: None,
+            game_result: None,
+            winning_team: None,
+            mode: options.mode,
+            cross_team_guesses: options.cross_team_guesses,
+            last_activity: Instant::now(),
+            last_spectator_poll: None,
+            last_match_poll: None,
+            index,
+            scheduler,
+            closed: false,
+            seed,
+            rng: GameRng::seed_from_u64(seed),
+            clock_mode: ClockMode::default(),
+        }))
     }
 
     /// Returns the public status of the game
@@ -143,7 +378,11 @@ impl GameState {
                 .values()
                 .map(|p| p.session.name.clone())
                 .collect(),
-            state: PublicInnerState::try_from(&self.state, &self.players)?,
+            bot_names: self.bot_names(),
+            host: self.host.clone(),
+            options: self.options(),
+            teams: self.teams(),
+            state: self.public_state()?,
         })
     }
 
@@ -161,11 +400,240 @@ impl GameState {
                 .values()
                 .map(|p| p.session.name.clone())
                 .collect(),
+            bot_names: self.bot_names(),
+            host: self.host.clone(),
+            options: self.options(),
+            teams: self.teams(),
             player_state: self.players.get(puuid).ok_or(Error::Unauthorized)?.state(),
-            state: PublicInnerState::try_from(&self.state, &self.players)?,
+            summoners: self
+                .players
+                .values()
+                .filter_map(|p| Some((p.session.name.clone(), p.binding.clone()?)))
+                .collect(),
+            state: self.public_state()?,
         })
     }
 
+    /// Returns the rules of the lobby
+    fn options(&self) -> LobbyOptions {
+        LobbyOptions {
+            mode: self.mode,
+            cross_team_guesses: self.cross_team_guesses,
+        }
+    }
+
+    /// Returns the names of the players controlled by the server
+    fn bot_names(&self) -> Vec<String> {
+        self.players
+            .values()
+            .filter(|p| p.bot.is_some())
+            .map(|p| p.session.name.clone())
+            .collect()
+    }
+
+    /// Returns the side of each player, empty unless both teams are played
+    fn teams(&self) -> HashMap<String, Team> {
+        self.players
+            .iter()
+            .filter_map(|(name, p)| Some((name.clone(), p.team?)))
+            .collect()
+    }
+
+    /// Returns the number of players in the given team
+    fn team_size(&self, team: Team) -> usize {
+        self.players
+            .values()
+            .filter(|p| p.team == Some(team))
+            .count()
+    }
+
+    /// Returns the side of a player joining the lobby, the least crowded one in versus lobbies
+    fn free_team(&self) -> Result<Option<Team>, Error> {
+        match self.mode {
+            LobbyMode::Single if self.player_count() < MAX_PLAYERS => Ok(None),
+            LobbyMode::Versus => [Team::Order, Team::Chaos]
+                .into_iter()
+                .filter(|team| self.team_size(*team) < MAX_PLAYERS)
+                .min_by_key(|team| self.team_size(*team))
+                .map(Some)
+                .ok_or(Error::MaxPlayerReached),
+            _ => Err(Error::MaxPlayerReached),
+        }
+    }
+
+    /// Returns the groups of players sharing a composition and voting together, with their names sorted
+    fn groups(&self) -> Vec<Vec<String>> {
+        let mut groups = HashMap::<_, Vec<_>>::new();
+        for (name, player) in self.players.iter() {
+            groups.entry(player.team).or_default().push(name.clone());
+        }
+
+        let mut groups = groups.into_iter().collect::<Vec<_>>();
+        groups.sort_by_key(|(team, _)| team.map(Team::team_id));
+        groups
+            .into_iter()
+            .map(|(_, mut names)| {
+                names.sort();
+                names
+            })
+            .collect()
+    }
+
+    /// Returns the status of the game, including the roles of the players
+    pub fn get_admin_status(&self) -> AdminGameStatus {
+        AdminGameStatus {
+            uid: self.uid,
+            seed: self.seed,
+            host: self.host.clone(),
+            options: self.options(),
+            state: self.state.kind(),
+            players: self
+                .players
+                .values()
+                .map(|p| AdminPlayerStatus {
+                    name: p.session.name.clone(),
+                    role: p.role(),
+                    binding: p.binding.clone(),
+                    pending_messages: p.proxy.messages.lock().unwrap().len(),
+                    bot: p.bot.as_ref().map(|b| b.strategy),
+                    team: p.team,
+                })
+                .collect(),
+            active_game: self.active_game.clone(),
+            has_match_result: self.match_result.is_some(),
+            game_result: self.game_result.clone(),
+            clock_mode: self.clock_mode,
+            game_time: match self.state {
+                InnerState::InGame { ref clock, .. } => Some(clock.now()),
+                _ => None,
+            },
+            game_paused: match self.state {
+                InnerState::InGame { ref clock, .. } => Some(clock.is_paused()),
+                _ => None,
+            },
+        }
+    }
+
+    /// Replaces the state of the game, logging the transition
+    fn set_state(&mut self, state: InnerState) {
+        tracing::info!(
+            game = %self.uid,
+            from = self.state.kind().as_str(),
+            to = state.kind().as_str(),
+            "State transition"
+        );
+        self.state = state;
+    }
+
+    /// Records an action on the game, delaying its collection, and schedules its next deadline accordingly
+    fn touch(&mut self) {
+        self.last_activity = Instant::now();
+        self.reschedule();
+    }
+
+    /// Schedules the next deadline of the game
+    fn reschedule(&self) {
+        self.scheduler.schedule(self.uid, self.next_deadline());
+    }
+
+    /// Returns the next instant at which the game must be ticked, if any
+    fn next_deadline(&self) -> Option<Instant> {
+        let next_poll = |last: Option<Instant>, interval| last.map(|t| t + interval);
+        let mut deadlines = Vec::new();
+
+        match self.state {
+            InnerState::Draft if self.polls_spectator() => {
+                deadlines.push(
+                    next_poll(self.last_spectator_poll, SPECTATOR_POLL_INTERVAL)
+                        .unwrap_or_else(Instant::now),
+                );
+            }
+            // The classes are initialized on the first tick
+            InnerState::InGame { data: None, .. } => deadlines.push(Instant::now()),
+            InnerState::InGame {
+                ref clock,
+                data: Some(ref info),
+            } => deadlines.extend(
+                self.players
+                    .values()
+                    .filter_map(Player::next_deadline)
+                    // Deadlines are only processed when the duration changes
+                    .filter_map(|secs| clock.instant_of(secs.max(info.duration + 1))),
+            ),
+            InnerState::Voting { deadline, .. } => deadlines.push(deadline),
+            _ => {}
+        }
+
+        if self.bots_pending() {
+            deadlines.push(Instant::now());
+        }
+
+        if self.polls_match() {
+            deadlines.push(
+                next_poll(self.last_match_poll, MATCH_POLL_INTERVAL).unwrap_or_else(Instant::now),
+            );
+        }
+
+        deadlines.into_iter().min()
+    }
+
+    /// Whether some bots have messages to read or a ballot to cast
+    fn bots_pending(&self) -> bool {
+        let votes = match self.state {
+            InnerState::Voting { ref votes, .. } => Some(votes),
+            _ => None,
+        };
+
+        self.players.iter().any(|(name, p)| {
+            p.bot.as_ref().is_some_and(|bot| {
+                !p.proxy.messages.lock().unwrap().is_empty()
+                    || votes.is_some_and(|votes| bot.votes() && !votes.contains_key(name))
+            })
+        })
+    }
+
+    /// Whether the players' game must be looked for through the spectator API
+    fn polls_spectator(&self) -> bool {
+        env_config().lol_api_key.is_some()
+            && matches!(self.state, InnerState::Draft)
+            && self.linked_summoner_ids().is_some()
+    }
+
+    /// Whether the result of the players' game must be fetched from the match-v5 API
+    fn polls_match(&self) -> bool {
+        env_config().lol_api_key.is_some()
+            && matches!(
+                self.state,
+                InnerState::InGame { .. } | InnerState::Voting { .. } | InnerState::End { .. }
+            )
+            && self.active_game.is_some()
+            && self.match_result.is_none()
+    }
+
+    /// Returns whether the game has been inactive long enough to be collected
+    ///
+    /// Lobbies and finished games have their own timeouts, shorter than the one of games in progress
+    pub fn is_expired(&self) -> bool {
+        let config = env_config();
+        let timeout = match self.state {
+            InnerState::Setup => config.lobby_idle_timeout,
+            InnerState::End { .. } => config.finished_game_retention,
+            _ => config.abandoned_game_timeout,
+        };
+
+        self.last_activity.elapsed() > Duration::from_secs(timeout)
+    }
+
+    /// Returns the current state of the game, without its data
+    pub fn state_kind(&self) -> StateKind {
+        self.state.kind()
+    }
+
+    /// Prevents players from joining the game, before removing it
+    pub fn close(&mut self) {
+        self.closed = true;
+    }
+
     /// Returns whether the player with the given uuid is currently in this game
     pub fn has_player(&self, puuid: &String) -> bool {
         self.players.contains_key(puuid)
@@ -173,7 +641,19 @@ impl GameState {
 
     /// Returns the number of players in the game
     pub fn player_count(&self) -> usize {
-        return self.players.len();
+        self.players.len()
+    }
+
+    /// Returns whether the lobby has enough players to be started, in each team when both are played
+    fn has_enough_players(&self) -> bool {
+        let sizes = match self.mode {
+            LobbyMode::Single => vec![self.player_count()],
+            LobbyMode::Versus => vec![self.team_size(Team::Order), self.team_size(Team::Chaos)],
+        };
+
+        sizes
+            .into_iter()
+            .all(|size| (MIN_PLAYERS..=MAX_PLAYERS).contains(&size))
     }
 
     /// Add a player to this game
@@ -187,14 +667,27 @@ impl GameState {
     ) -> Result<(), Error> {
         require_state!(InnerState::Setup, self);
 
-        if self.player_count() == 5 {
-            return Err(Error::MaxPlayerReached);
+        if self.closed {
+            return Err(Error::NotFound);
+        }
+
+        // Joining again is a no-op, which must not move the player to another team
+        if self.players.contains_key(&session.name) {
+            return Ok(());
         }
 
-        if let hash_map::Entry::Vacant(e) = self.players.entry(session.name.clone()) {
-            e.insert(Player::new(session.clone(), proxy));
+        let team = self.free_team()?;
+        self.index.insert(&session.name, self.uid)?;
+
+        let mut player = Player::new(session.clone(), proxy);
+        player.team = team;
+        self.players.insert(session.name.clone(), player);
+
+        if self.host.is_none() {
+            self.host = Some(session.name);
         }
 
+        self.touch();
         Ok(())
     }
 
@@ -204,13 +697,153 @@ impl GameState {
     pub async fn remove_player(&mut self, puuid: String) -> Result<(), Error> {
         match self.state {
             InnerState::Setup | InnerState::End { .. } => {
-                if let Some(_) = self.players.remove(&puuid) {
+                if self.players.remove(&puuid).is_some() {
+                    self.index.remove(&puuid, self.uid);
+
+                    // Bots cannot keep a lobby alive on their own
+                    if self.players.values().all(|p| p.bot.is_some()) {
+                        for name in self.players.keys() {
+                            self.index.remove(name, self.uid);
+                        }
+                        self.players.clear();
+                    }
+
+                    if self.host.as_ref() == Some(&puuid) {
+                        self.host = self
+                            .players
+                            .iter()
+                            .find(|(_, p)| p.bot.is_none())
+                            .map(|(name, _)| name.clone());
+                    }
+                    self.touch();
                     Ok(())
                 } else {
                     Err(Error::NotInGame)
                 }
             }
-            _ => Err(Error::IncorrectState),
+            // Roles are drawn for the players of the lobby, and the votes wait for each of them
+            InnerState::Draft | InnerState::InGame { .. } | InnerState::Voting { .. } => {
+                Err(Error::IncorrectState)
+            }
+        }
+    }
+
+    /// Adds a bot to the lobby, named after the first free "Bot <n>"
+    ///
+    /// Returns the name of the bot
+    ///
+    /// - host: Name of the player making the request, who must be the host
+    /// - strategy: How the bot votes
+    pub fn add_bot(&mut self, host: &str, strategy: VoteStrategy) -> Result<String, Error> {
+        require_state!(InnerState::Setup, self);
+
+        if self.host.as_deref() != Some(host) {
+            return Err(Error::Unauthorized);
+        }
+
+        let team = self.free_team()?;
+
+        // Bots are indexed like the other players, so that nobody joins a game under the same name
+        let name = (1..)
+            .map(|i| format!("{}{i}", bot::NAME_PREFIX))
+            .find(|name| {
+                !self.players.contains_key(name) && self.index.insert(name, self.uid).is_ok()
+            })
+            .expect("a free bot name");
+
+        let rng = GameRng::from_rng(&mut self.rng).map_err(Error::from)?;
+        let mut bot = Player::new_bot(name.clone(), Bot::new(strategy, rng));
+        bot.team = team;
+        self.players.insert(name.clone(), bot);
+
+        self.touch();
+        Ok(name)
+    }
+
+    /// Removes a bot from the lobby
+    ///
+    /// - host: Name of the player making the request, who must be the host
+    /// - name: Name of the bot to remove
+    pub async fn remove_bot(&mut self, host: &str, name: &str) -> Result<(), Error> {
+        require_state!(InnerState::Setup, self);
+
+        if self.host.as_deref() != Some(host) {
+            return Err(Error::Unauthorized);
+        }
+
+        match self.players.get(name) {
+            Some(player) if player.bot.is_some() => self.remove_player(name.to_owned()).await,
+            _ => Err(Error::NotFound),
+        }
+    }
+
+    /// Moves a player to the other team of a versus lobby
+    ///
+    /// - name: Name of the player making the request, only the host can move other players
+    /// - player: Name of the player to move
+    /// - team: Side to which the player moves
+    pub fn set_team(&mut self, name: &str, player: &str, team: Team) -> Result<(), Error> {
+        require_state!(InnerState::Setup, self);
+
+        if self.mode != LobbyMode::Versus {
+            return Err(Error::IncorrectState);
+        }
+
+        if name != player && self.host.as_deref() != Some(name) {
+            return Err(Error::Unauthorized);
+        }
+
+        let current = self.players.get(player).ok_or(Error::NotFound)?.team;
+        if current != Some(team) && self.team_size(team) >= MAX_PLAYERS {
+            return Err(Error::MaxPlayerReached);
+        }
+
+        if let Some(p) = self.players.get_mut(player) {
+            p.team = Some(team);
+        }
+
+        self.touch();
+        Ok(())
+    }
+
+    /// Lets the bots read their messages, and cast their ballot once the votes are open
+    async fn play_bots(&mut self) {
+        for player in self.players.values() {
+            if let Some(ref bot) = player.bot {
+                bot.consume(&player.proxy);
+            }
+        }
+
+        let InnerState::Voting { ref votes, .. } = self.state else {
+            return;
+        };
+
+        // Bots only vote for their teammates
+        let mut roles = HashMap::<_, HashMap<_, _>>::new();
+        for (name, player) in self.players.iter() {
+            roles
+                .entry(player.team)
+                .or_default()
+                .insert(name.clone(), player.role());
+        }
+
+        let mut ballots = Vec::new();
+        for (name, player) in self.players.iter_mut() {
+            let Some(ref mut bot) = player.bot else {
+                continue;
+            };
+
+            if !votes.contains_key(name) {
+                if let Some(ballot) = bot.ballots(name, &roles[&player.team]) {
+                    ballots.push((name.clone(), ballot));
+                }
+            }
+        }
+
+        for (name, ballot) in ballots {
+            if let Err(e) = self.add_votes(name, ballot).await {
+                tracing::error!("Could not add the votes of a bot: {:?}", e)
+            }
         }
     }
 
@@ -232,39 +865,309 @@ impl GameState {
         }
     }
 
-    /// Start the game by creating and assigning roles
-    pub async fn start(&mut self) -> Result<(), Error> {
-        match self.state {
-            InnerState::Setup => {
-                if self.player_count() != 5 {
-                    return Err(Error::NotEnoughPlayers);
+    /// Update the state of the game with data uploaded from a player's LoL client
+    ///
+    /// - name: Name of the player who uploaded the data
+    pub async fn receive_live_data(
+        &mut self,
+        name: &str,
+        live: MergedGameData,
+    ) -> Result<(), Error> {
+        require_state!(InnerState::InGame { ref data, .. }, self);
+
+        // The uploader is the only player for which the client sends the active player data
+        let uploader = live.all_players.iter().find(|p| p.active_player.is_some());
+        let uploader_summoner = uploader.map(|p| p.summoner_name.clone());
+        let uploader_team = uploader.map(|p| p.team);
+
+        let game_end = live.events.events.iter().find_map(|e| match e.data {
+            EventData::GameEnd { ref result } => Some(result.clone()),
+            _ => None,
+        });
+
+        let mut game_info = data.clone().unwrap_or_default();
+        if let InnerState::InGame { ref mut clock, .. } = self.state {
+            clock.observe(self.clock_mode, live.game_data.game_time);
+            game_info.duration = clock.now();
+        }
+        game_info.live = Some(match game_info.live.take() {
+            Some(previous) => live.merge(&previous),
+            None => live,
+        });
+        if let Some(ref live) = game_info.live {
+            self.bind_summoners(live, name, uploader_summoner);
+        }
+        self.update_state(game_info).await;
+        self.touch();
+
+        if let Some(result) = game_end {
+            self.winning_team = uploader_team.map(|team| match result {
+                GameResult::Win => team,
+                GameResult::Loss => team.opponent(),
+            });
+            // The result is given for the team of the uploader, which is only the team of everyone in single lobbies
+            if self.mode == LobbyMode::Single {
+                self.game_result = Some(result);
+            }
+            self.finish_game()?;
+        }
+
+        Ok(())
+    }
+
+    /// Binds the players to their summoner in the live data
+    ///
+    /// A player is identified by their own upload first, then by a previous binding, and finally through their linked account
+    fn bind_summoners(
+        &mut self,
+        live: &MergedGameData,
+        uploader: &str,
+        uploader_summoner: Option<String>,
+    ) {
+        for (name, player) in self.players.iter_mut() {
+            let summoner = uploader_summoner
+                .as_ref()
+                .filter(|_| name == uploader)
+                .and_then(|s| live.all_players.iter().find(|p| &p.summoner_name == s))
+                .or_else(|| {
+                    let binding = player.binding.as_ref()?;
+                    live.all_players
+                        .iter()
+                        .find(|p| p.summoner_name == binding.summoner_name)
+                })
+                .or_else(|| {
+                    let account = player.session.account.as_ref()?;
+                    live.all_players
+                        .iter()
+                        .find(|p| account.is_summoner_name(&p.summoner_name))
+                });
+
+            match summoner {
+                Some(summoner) if player.team.is_some_and(|team| team != summoner.team) => {
+                    tracing::warn!(
+                        player = %name,
+                        summoner = %summoner.summoner_name,
+                        "Summoner is not on the side of the player"
+                    );
                 }
+                Some(summoner) => player.binding = Some(SummonerBinding::from(summoner)),
+                None => {}
+            }
+        }
+    }
+
+    /// Manually binds a player to a summoner, when they cannot be identified automatically
+    ///
+    /// - host: Name of the player making the request, who must be the host
+    /// - name: Name of the player to bind
+    /// - summoner_name: Name of the summoner played by this player
+    pub fn bind_summoner(
+        &mut self,
+        host: &str,
+        name: &str,
+        summoner_name: String,
+    ) -> Result<(), Error> {
+        if self.host.as_deref() != Some(host) {
+            return Err(Error::Unauthorized);
+        }
+
+        let player = self.players.get(name).ok_or(Error::NotFound)?;
+        let summoner = self.state.live_data().and_then(|live| {
+            live.all_players
+                .iter()
+                .find(|p| p.summoner_name == summoner_name)
+        });
 
-                let composition = team_builder::generate_composition();
+        if let (Some(team), Some(summoner)) = (player.team, summoner) {
+            if summoner.team != team {
+                return Err(Error::WrongTeam);
+            }
+        }
 
-                for (player, role) in self.players.values_mut().zip(composition.iter()) {
-                    player.set_role(*role)?;
+        if self.players.iter().any(|(n, p)| {
+            n != name
+                && p.binding
+                    .as_ref()
+                    .is_some_and(|b| b.summoner_name == summoner_name)
+        }) {
+            return Err(Error::SummonerAlreadyBound);
+        }
+
+        let binding = summoner
+            .map(SummonerBinding::from)
+            .unwrap_or(SummonerBinding {
+                summoner_name,
+                champion_name: None,
+                position: None,
+            });
+
+        self.players.get_mut(name).ok_or(Error::NotFound)?.binding = Some(binding);
+
+        self.touch();
+        Ok(())
+    }
+
+    /// Creates a composition for each team and assigns a role to each player
+    ///
+    /// Players are sorted by name, so that the roles only depend on the seed of the game, and each class draws its
+    /// own generator from the one of the game, so that its timings do not depend on the order of the updates.
+    fn assign_roles(&mut self) -> Result<(), Error> {
+        for names in self.groups() {
+            let mut composition = team_builder::generate_composition(&mut self.rng, names.len());
+            composition.shuffle(&mut self.rng);
+
+            for (name, role) in names.iter().zip(composition) {
+                let rng = GameRng::from_rng(&mut self.rng).map_err(Error::from)?;
+                if let Some(player) = self.players.get_mut(name) {
+                    player.set_role(role, rng)?;
                 }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Changes the clock of the game, for the current League game if any and the following ones
+    ///
+    /// - mode: New mode of the clock, keeping the current game time
+    /// - game_time: Game time to jump to, in seconds, only allowed for manual clocks
+    pub fn set_clock(
+        &mut self,
+        mode: Option<ClockMode>,
+        game_time: Option<u64>,
+    ) -> Result<(), Error> {
+        let mode = mode.unwrap_or(self.clock_mode);
+        if game_time.is_some()
+            && (mode != ClockMode::Manual || !matches!(self.state, InnerState::InGame { .. }))
+        {
+            return Err(Error::IncorrectState);
+        }
+
+        self.clock_mode = mode;
+        if let InnerState::InGame { ref mut clock, .. } = self.state {
+            *clock = clock.with_mode(mode);
+            if let Some(game_time) = game_time {
+                clock.set(game_time)?;
+            }
+        }
+
+        self.touch();
+        Ok(())
+    }
 
-                self.state = InnerState::Draft {};
+    /// Moves the game to the given state, regardless of the current one
+    ///
+    /// Roles are assigned if needed, or removed when going back to setup. The new state is sent to all the players.
+    pub fn force_state(&mut self, kind: StateKind) -> Result<(), Error> {
+        if kind == StateKind::Setup {
+            self.players.values_mut().for_each(Player::reset_role);
+            self.active_game = None;
+            self.match_result = None;
+            self.game_result = None;
+            self.winning_team = None;
+        } else if self.players.values().any(|p| p.role().is_none()) {
+            if !self.has_enough_players() {
+                return Err(Error::NotEnoughPlayers);
             }
-            InnerState::Draft => {
-                self.state = InnerState::InGame {
-                    start: Instant::now(),
-                    data: None,
+
+            self.players.values_mut().for_each(Player::reset_role);
+            self.assign_roles()?;
+        }
+
+        let state = match kind {
+            StateKind::Setup => InnerState::Setup,
+            StateKind::Draft => InnerState::Draft,
+            StateKind::InGame => InnerState::InGame {
+                clock: GameClock::new(self.clock_mode, Duration::ZERO),
+                data: None,
+            },
+            StateKind::Voting => InnerState::Voting {
+                votes: Default::default(),
+                deadline: Instant::now() + Duration::from_secs(env_config().vote_timeout),
+            },
+            StateKind::End => InnerState::End {
+                votes: match self.state {
+                    InnerState::Voting { ref votes, .. } | InnerState::End { ref votes } => {
+                        votes.clone()
+                    }
+                    _ => Default::default(),
+                },
+            },
+        };
+        self.set_state(state);
+
+        self.touch();
+
+        self.broadcast_state()
+    }
+
+    /// Returns the state of the game, as seen by the players
+    fn public_state(&self) -> Result<PublicInnerState, Error> {
+        PublicInnerState::try_from(
+            &self.state,
+            &self.players,
+            self.match_result.as_ref(),
+            self.game_result.as_ref(),
+            self.winning_team,
+        )
+    }
+
+    /// Sends the current state to all the players
+    fn broadcast_state(&self) -> Result<(), Error> {
+        let state = self.public_state()?;
+        for player in self.players.values() {
+            player.proxy.send_message(messages::Message::State {
+                state: state.clone(),
+            });
+        }
+
+        Ok(())
+    }
+
+    /// Moves the game to voting once the League game is over, without waiting for the players to end it
+    fn finish_game(&mut self) -> Result<(), Error> {
+        if !matches!(self.state, InnerState::InGame { .. }) {
+            return Ok(());
+        }
+
+        self.set_state(InnerState::Voting {
+            votes: Default::default(),
+            deadline: Instant::now() + Duration::from_secs(env_config().vote_timeout),
+        });
+        self.touch();
+        self.broadcast_state()
+    }
+
+    /// Start the game by creating and assigning roles
+    pub async fn start(&mut self) -> Result<(), Error> {
+        match self.state {
+            InnerState::Setup => {
+                if !self.has_enough_players() {
+                    return Err(Error::NotEnoughPlayers);
                 }
+
+                self.assign_roles()?;
+                self.set_state(InnerState::Draft);
             }
+            InnerState::Draft => self.set_state(InnerState::InGame {
+                clock: GameClock::new(self.clock_mode, Duration::ZERO),
+                data: None,
+            }),
             _ => return Err(Error::IncorrectState),
         }
+
+        self.touch();
         Ok(())
     }
 
     pub async fn end(&mut self) -> Result<(), Error> {
-        if let InnerState::InGame { start, .. } = self.state {
-            if start.elapsed().as_secs() > 10 {
-                self.state = InnerState::Voting {
+        if let InnerState::InGame { ref clock, .. } = self.state {
+            if clock.now() > 10 {
+                self.set_state(InnerState::Voting {
                     votes: Default::default(),
-                };
+                    deadline: Instant::now() + Duration::from_secs(env_config().vote_timeout),
+                });
+                self.touch();
                 Ok(())
             } else {
                 Err(Error::IncorrectState)
@@ -280,22 +1183,79 @@ impl GameState {
         ballots: HashMap<String, Role>,
     ) -> Result<(), Error> {
         match self.state {
-            InnerState::Voting { ref mut votes } => {
-                if votes.len() != 5 && !votes.contains_key(&name) {
-                    votes.insert(name.clone(), ballots);
+            InnerState::Voting { ref votes, .. } if votes.contains_key(&name) => {
+                return Err(Error::VotesClosed)
+            }
+            InnerState::Voting { .. } => {}
+            _ => return Err(Error::IncorrectState),
+        }
+        self.check_ballots(&name, &ballots)?;
 
-                    if votes.len() == 5 {
-                        self.state = InnerState::End {
-                            votes: votes.clone(),
-                        }
-                    }
+        require_state!(InnerState::Voting { ref mut votes, .. }, self);
+        votes.insert(name.clone(), ballots);
 
-                    Ok(())
-                } else {
-                    Err(Error::VotesClosed)
-                }
+        let groups = self.groups();
+        let completed = groups
+            .iter()
+            .filter(|group| self.has_voted(group))
+            .collect::<Vec<_>>();
+        if let Some(group) = completed.iter().find(|group| group.contains(&name)) {
+            for name in group.iter() {
+                self.players[name]
+                    .proxy
+                    .send_message(messages::Message::VotesCompleted);
+            }
+        }
+        if completed.len() == groups.len() {
+            self.close_votes();
+        }
+
+        self.touch();
+        Ok(())
+    }
+
+    /// Checks that a ballot only guesses the roles of the other players of the group of the voter, unless the lobby
+    /// allows guessing the roles of the opponents
+    fn check_ballots(&self, name: &str, ballots: &HashMap<String, Role>) -> Result<(), Error> {
+        let team = self.players.get(name).ok_or(Error::NotInGame)?.team;
+
+        for target in ballots.keys() {
+            let player = self.players.get(target).ok_or(Error::InvalidBallot)?;
+            if target == name || (player.team != team && !self.cross_team_guesses) {
+                return Err(Error::InvalidBallot);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Whether all the players of a group cast their ballot
+    fn has_voted(&self, group: &[String]) -> bool {
+        match self.state {
+            InnerState::Voting { ref votes, .. } => group.iter().all(|n| votes.contains_key(n)),
+            _ => false,
+        }
+    }
+
+    /// Ends the vote with the ballots received so far
+    fn close_votes(&mut self) {
+        // The groups which completed their votes were notified already
+        let pending = self
+            .groups()
+            .into_iter()
+            .filter(|group| !self.has_voted(group))
+            .flatten()
+            .collect::<Vec<_>>();
+
+        if let InnerState::Voting { ref votes, .. } = self.state {
+            let votes = votes.clone();
+            self.set_state(InnerState::End { votes });
+
+            for name in pending {
+                self.players[&name]
+                    .proxy
+                    .send_message(messages::Message::VotesCompleted);
             }
-            _ => Err(Error::IncorrectState),
         }
     }
 
@@ -326,19 +1286,173 @@ impl GameState {
         }
     } */
 
-    async fn fetch_updates(state: Weak<RwLock<Self>>) {
-        loop {
-            tokio::time::sleep(Duration::from_secs(3)).await;
+    /// Returns the summoner ids of all the players, if they all linked their account
+    fn linked_summoner_ids(&self) -> Option<Vec<String>> {
+        self.players
+            .values()
+            .map(|p| p.session.account.as_ref().map(|a| a.summoner_id.clone()))
+            .collect()
+    }
+
+    /// Moves the game to [InnerState::InGame] if all the players are in the given game, in the same team or on their
+    /// side in versus lobbies
+    fn detect_game(&mut self, info: &CurrentGameInfo) {
+        if !matches!(self.state, InnerState::Draft) {
+            return;
+        }
+
+        // Side of each player in the lobby, and team id of their summoner in the game
+        let teams = self
+            .players
+            .values()
+            .map(|p| {
+                let id = &p.session.account.as_ref()?.summoner_id;
+                let participant = info.participants.iter().find(|p| &p.summoner_id == id)?;
+                Some((p.team, participant.team_id))
+            })
+            .collect::<Option<Vec<_>>>();
 
-            if let Some(state) = state.upgrade() {
+        let detected = teams.is_some_and(|teams| match self.mode {
+            LobbyMode::Single => teams.windows(2).all(|w| w[0].1 == w[1].1),
+            LobbyMode::Versus => teams
+                .iter()
+                .all(|(team, id)| team.map(Team::team_id) == Some(*id)),
+        });
+        if !detected {
+            return;
+        }
+
+        // The start time is only set once the loading screen is over
+        let elapsed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .zip(u64::try_from(info.game_start_time).ok().filter(|t| *t > 0))
+            .and_then(|(now, start)| now.checked_sub(Duration::from_millis(start)))
+            .unwrap_or_default();
+
+        self.active_game = Some(ActiveGame {
+            game_id: info.game_id,
+            platform_id: info.platform_id.clone(),
+            game_start_time: info.game_start_time,
+        });
+        self.set_state(InnerState::InGame {
+            clock: GameClock::new(self.clock_mode, elapsed),
+            data: None,
+        });
+        self.touch();
+    }
+
+    /// Looks for the League game of the players while in draft
+    async fn poll_active_game(state: &RwLock<Self>) -> Result<(), Error> {
+        let summoner_ids = {
+            let lock = state.read().await;
+            if !matches!(lock.state, InnerState::Draft) {
+                return Ok(());
+            }
+            lock.linked_summoner_ids()
+        };
+
+        let Some(summoner_id) = summoner_ids.and_then(|ids| ids.into_iter().next()) else {
+            return Ok(());
+        };
+
+        match spectator::get_active_game(summoner_id).await {
+            Ok(info) => {
+                state.write().await.detect_game(&info);
+                Ok(())
+            }
+            Err(Error::NotFound | Error::LolApiDisabled) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Fetches the result of the League game once it is over
+    async fn poll_match_result(state: &RwLock<Self>) -> Result<(), Error> {
+        let match_id = {
+            let lock = state.read().await;
+            match (&lock.state, &lock.active_game, &lock.match_result) {
+                (
+                    InnerState::InGame { .. } | InnerState::Voting { .. } | InnerState::End { .. },
+                    Some(game),
+                    None,
+                ) => format!("{}_{}", game.platform_id, game.game_id),
+                _ => return Ok(()),
+            }
+        };
+
+        match matches::get_match(&match_id).await {
+            Ok(dto) => {
                 let mut lock = state.write().await;
-                if let InnerState::InGame { start, .. } = lock.state {
-                    lock.update_state(GameInfo {
-                        duration: start.elapsed().as_secs(),
-                    })
-                    .await;
+                let result = MatchResult::from(dto);
+
+                for player in lock.players.values() {
+                    if let Err(e) = player.receive_result(&result) {
+                        tracing::error!("Could not process match result: {:?}", e)
+                    }
                 }
+                lock.match_result = Some(result);
+
+                // The match is only available once it is over
+                lock.finish_game()
+            }
+            Err(Error::NotFound | Error::LolApiDisabled) => Ok(()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Processes the deadlines of the game which are due, then schedules the next one
+    pub async fn tick(state: Arc<RwLock<Self>>) {
+        let (poll_spectator, poll_match) = {
+            let mut lock = state.write().await;
+            let now = Instant::now();
+            let due =
+                |last: Option<Instant>, interval| !matches!(last, Some(t) if now < t + interval);
+
+            let poll_spectator =
+                lock.polls_spectator() && due(lock.last_spectator_poll, SPECTATOR_POLL_INTERVAL);
+            if poll_spectator {
+                lock.last_spectator_poll = Some(now);
+            }
+
+            let poll_match = lock.polls_match() && due(lock.last_match_poll, MATCH_POLL_INTERVAL);
+            if poll_match {
+                lock.last_match_poll = Some(now);
+            }
+
+            (poll_spectator, poll_match)
+        };
+
+        if poll_spectator {
+            if let Err(e) = Self::poll_active_game(&state).await {
+                tracing::error!("Could not fetch active game: {:?}", e)
+            }
+        }
+
+        if poll_match {
+            if let Err(e) = Self::poll_match_result(&state).await {
+                tracing::error!("Could not fetch match result: {:?}", e)
+            }
+        }
+
+        let mut lock = state.write().await;
+        lock.play_bots().await;
+        match lock.state {
+            InnerState::InGame {
+                ref clock,
+                ref data,
+            } => {
+                let game_info = GameInfo {
+                    duration: clock.now(),
+                    ..data.clone().unwrap_or_default()
+                };
+                lock.update_state(game_info).await;
+            }
+            InnerState::Voting { deadline, .. } if deadline <= Instant::now() => {
+                lock.close_votes();
+                lock.touch();
             }
+            _ => {}
         }
+        lock.reschedule();
     }
 }
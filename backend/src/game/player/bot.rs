@@ -0,0 +1,86 @@
+use std::collections::HashMap;
+
+use rand::seq::SliceRandom;
+use serde::{Deserialize, Serialize};
+
+use crate::game::{messages::Message, team_builder::Role, GameRng};
+
+use super::proxy::PlayerProxy;
+
+/// Start of the names of the bots, which players cannot log in with
+pub const NAME_PREFIX: &str = "Bot ";
+
+/// How a bot fills its ballot
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum VoteStrategy {
+    /// Never votes, the votes are closed by their deadline
+    Abstain,
+    /// Guesses a random role for each other player
+    #[default]
+    Random,
+    /// Votes the actual role of each other player
+    Omniscient,
+}
+
+/// Player controlled by the server, filling a lobby when there are not enough people
+#[derive(Debug)]
+pub struct Bot {
+    pub strategy: VoteStrategy,
+    rng: GameRng,
+}
+
+impl Bot {
+    /// - rng: Generator of the random guesses
+    pub fn new(strategy: VoteStrategy, rng: GameRng) -> Self {
+        Self { strategy, rng }
+    }
+
+    /// Whether the bot casts a ballot when the votes open
+    pub fn votes(&self) -> bool {
+        self.strategy != VoteStrategy::Abstain
+    }
+
+    /// Reads the messages sent to the bot, so that they do not pile up in its proxy
+    pub fn consume(&self, proxy: &PlayerProxy) {
+        let messages = std::mem::take(&mut *proxy.messages.lock().unwrap());
+
+        for message in messages {
+            if let Message::Role { role } = message {
+                tracing::debug!(bot = %proxy.name, ?role, "Bot received its role");
+            }
+        }
+    }
+
+    /// Fills the ballot of the bot, if its strategy votes
+    ///
+    /// - name: Name of the bot, which does not vote for itself
+    /// - roles: Role of each player of the game
+    pub fn ballots(
+        &mut self,
+        name: &str,
+        roles: &HashMap<String, Option<Role>>,
+    ) -> Option<HashMap<String, Role>> {
+        // Sorted, so that the random guesses only depend on the seed of the game
+        let mut others = roles
+            .iter()
+            .filter(|(n, _)| n.as_str() != name)
+            .collect::<Vec<_>>();
+        others.sort_by_key(|(n, _)| *n);
+        let others = others.into_iter();
+
+        match self.strategy {
+            VoteStrategy::Abstain => None,
+            VoteStrategy::Random => Some(
+                others
+                    .filter_map(|(n, _)| Some((n.clone(), *Role::ALL.choose(&mut self.rng)?)))
+                    .collect(),
+            ),
+            VoteStrategy::Omniscient => Some(
+                others
+                    .filter_map(|(n, role)| Some((n.clone(), (*role)?)))
+                    .collect(),
+            ),
+        }
+    }
+}
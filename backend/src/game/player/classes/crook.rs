@@ -1,7 +1,10 @@
 use serde::Serialize;
 
 
-use crate::game::{GameInfo, GameInfoMutation};
+use crate::game::{
+    match_result::{MatchResult, ParticipantResult},
+    GameInfo, GameInfoMutation,
+};
 
 use super::Class;
 
@@ -28,6 +31,10 @@ impl Class for Crook {
     fn state(&self) -> super::PlayerState {
         super::PlayerState::Crook(CrookState)
     }
+
+    fn objective(&self, participant: &ParticipantResult, _result: &MatchResult) -> Option<bool> {
+        Some(participant.win)
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -2,12 +2,12 @@ use std::sync::Mutex;
 
 use num_derive::FromPrimitive;
 use num_traits::FromPrimitive;
-use rand::{thread_rng, Rng};
+use rand::Rng;
 use rand_distr::{Distribution, Normal};
 use serde::Serialize;
 
 use crate::{
-    game::{messages::Message, GameInfo, GameInfoMutation},
+    game::{messages::Message, GameInfo, GameInfoMutation, GameRng},
     routes::error::Error,
 };
 
@@ -37,9 +37,19 @@ struct State {
     next_mission_timestamp: f64,
 }
 
-#[derive(Default, Debug)]
+#[derive(Debug)]
 pub struct Droid {
     state: Mutex<State>,
+    rng: Mutex<GameRng>,
+}
+
+impl Droid {
+    pub fn new(rng: GameRng) -> Self {
+        Self {
+            state: Default::default(),
+            rng: Mutex::new(rng),
+        }
+    }
 }
 
 impl Class for Droid {
@@ -49,10 +59,11 @@ impl Class for Droid {
         _player: &crate::game::player::Player,
     ) -> Result<(), crate::routes::error::Error> {
         let mut lock = self.state.lock().unwrap();
+        let mut rng = self.rng.lock().unwrap();
 
         lock.next_mission_timestamp = Normal::new(600.0, 120.0)
             .map_err(Error::from)?
-            .sample(&mut thread_rng());
+            .sample(&mut *rng);
 
         Ok(())
     }
@@ -68,16 +79,20 @@ impl Class for Droid {
         #[allow(irrefutable_let_patterns)]
         if let GameInfoMutation::Duration((_, new_time)) = mutation {
             if lock.next_mission_timestamp <= *new_time as f64 {
+                let mut rng = self.rng.lock().unwrap();
+
                 lock.next_mission_timestamp += Normal::new(300.0, 120.0)
                     .map_err(Error::from)?
-                    .sample(&mut thread_rng());
+                    .sample(&mut *rng);
 
-                lock.mission =
-                    <Mission as FromPrimitive>::from_usize(thread_rng().gen_range(0..12));
+                lock.mission = <Mission as FromPrimitive>::from_usize(rng.gen_range(0..12));
 
-                println!(
-                    "Assigning new mission: {:?} at {}",
-                    lock.mission, new_time
+                tracing::debug!(
+                    player = %player.session.name,
+                    mission = ?lock.mission,
+                    game_time = new_time,
+                    next_mission = lock.next_mission_timestamp,
+                    "Assigning new mission"
                 );
 
                 player.proxy.send_message(Message::Mission {
@@ -89,6 +104,11 @@ impl Class for Droid {
         Ok(())
     }
 
+    fn next_deadline(&self) -> Option<u64> {
+        let next = self.state.lock().unwrap().next_mission_timestamp;
+        Some(next.max(0.0).ceil() as u64)
+    }
+
     fn state(&self) -> super::PlayerState {
         PlayerState::Droid(DroidState {
             mission: self.state.lock().unwrap().mission.clone(),
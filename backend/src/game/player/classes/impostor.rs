@@ -1,7 +1,10 @@
 use serde::Serialize;
 
 
-use crate::game::{GameInfoMutation, GameInfo};
+use crate::game::{
+    match_result::{MatchResult, ParticipantResult},
+    GameInfoMutation, GameInfo,
+};
 
 use super::Class;
 
@@ -28,6 +31,10 @@ impl Class for Impostor {
     fn state(&self) -> super::PlayerState {
         super::PlayerState::Impostor(ImpostorState)
     }
+
+    fn objective(&self, participant: &ParticipantResult, _result: &MatchResult) -> Option<bool> {
+        Some(!participant.win)
+    }
 }
 
 #[derive(Debug, Serialize)]
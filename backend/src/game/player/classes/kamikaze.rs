@@ -1,12 +1,35 @@
-use serde::Serialize;
+use std::sync::Mutex;
 
+use serde::Serialize;
 
-use crate::game::{GameInfoMutation, GameInfo};
+use crate::game::{
+    match_result::{MatchResult, ParticipantResult},
+    messages::Message,
+    GameInfo, GameInfoMutation,
+};
 
 use super::Class;
 
+/// Minimum game time between two rank updates sent to the player, in seconds
+const RANK_UPDATE_INTERVAL: u64 = 60;
+
+#[derive(Default, Debug)]
+struct State {
+    rank: KamikazeState,
+    last_sent: KamikazeState,
+    next_update: u64,
+}
+
 #[derive(Default, Debug)]
-pub struct Kamikaze {}
+pub struct Kamikaze {
+    state: Mutex<State>,
+}
+
+/// Rank of `own` among `values`, 1 being the highest
+fn rank<T: PartialOrd>(values: impl Iterator<Item = T>, own: T) -> usize {
+    1 + values.filter(|v| *v > own).count()
+}
+
 impl Class for Kamikaze {
     fn init(
         &self,
@@ -19,17 +42,85 @@ impl Class for Kamikaze {
     fn update(
         &self,
         _mutation: &GameInfoMutation,
-        _game_data: &GameInfo,
-        _player: &crate::game::player::Player,
+        game_data: &GameInfo,
+        player: &crate::game::player::Player,
     ) -> Result<(), crate::routes::error::Error> {
+        let (Some(live), Some(name)) = (&game_data.live, player.summoner_name()) else {
+            return Ok(());
+        };
+        let Some(own) = live.all_players.iter().find(|p| p.summoner_name == name) else {
+            return Ok(());
+        };
+
+        let mut lock = self.state.lock().unwrap();
+
+        lock.rank.deaths_rank = Some(rank(
+            live.all_players
+                .iter()
+                .filter(|p| p.team == own.team)
+                .map(|p| p.scores.deaths),
+            own.scores.deaths,
+        ));
+
+        if lock.next_update <= game_data.duration && lock.rank != lock.last_sent {
+            lock.next_update = game_data.duration + RANK_UPDATE_INTERVAL;
+            lock.last_sent = lock.rank.clone();
+
+            player.proxy.send_message(Message::KamikazeRank {
+                rank: lock.rank.clone(),
+            });
+        }
+
         Ok(())
     }
 
     fn state(&self) -> super::PlayerState {
-        super::PlayerState::Kamikaze(KamikazeState)
+        super::PlayerState::Kamikaze(self.state.lock().unwrap().rank.clone())
+    }
+
+    fn receive_result(
+        &self,
+        participant: &ParticipantResult,
+        result: &MatchResult,
+        player: &crate::game::player::Player,
+    ) -> Result<(), crate::routes::error::Error> {
+        let mut lock = self.state.lock().unwrap();
+
+        lock.rank = KamikazeState {
+            deaths_rank: Some(rank(
+                result.team(participant.team_id).map(|p| p.deaths),
+                participant.deaths,
+            )),
+            damage_rank: Some(rank(
+                result.team(participant.team_id).map(|p| p.damage),
+                participant.damage,
+            )),
+        };
+        lock.last_sent = lock.rank.clone();
+
+        player.proxy.send_message(Message::KamikazeRank {
+            rank: lock.rank.clone(),
+        });
+
+        Ok(())
+    }
+
+    fn objective(&self, participant: &ParticipantResult, result: &MatchResult) -> Option<bool> {
+        let teammates = || result.team(participant.team_id);
+
+        Some(
+            participant.win
+                && rank(teammates().map(|p| p.deaths), participant.deaths) == 1
+                && rank(teammates().map(|p| p.damage), participant.damage) == 1,
+        )
     }
 }
 
-#[derive(Debug, Serialize)]
+/// Rank of the Kamikaze within their team, 1 being the most
+#[derive(Debug, Serialize, Default, Clone, PartialEq, Eq)]
 #[serde(rename_all = "snake_case")]
-pub struct KamikazeState;
+pub struct KamikazeState {
+    deaths_rank: Option<usize>,
+    /// Only known once the match result is available
+    damage_rank: Option<usize>,
+}
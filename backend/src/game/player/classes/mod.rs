@@ -1,7 +1,11 @@
 use serde::Serialize;
 
 use crate::{
-    game::{team_builder::Role, GameInfo, GameInfoMutation},
+    game::{
+        match_result::{MatchResult, ParticipantResult},
+        team_builder::Role,
+        GameInfo, GameInfoMutation, GameRng,
+    },
     routes::error::Error,
 };
 
@@ -58,6 +62,26 @@ trait Class {
         player: &Player,
     ) -> Result<(), Error>;
     fn state(&self) -> PlayerState;
+
+    /// Called once the result of the match is available
+    fn receive_result(
+        &self,
+        _participant: &ParticipantResult,
+        _result: &MatchResult,
+        _player: &Player,
+    ) -> Result<(), Error> {
+        Ok(())
+    }
+
+    /// Whether the objective of the class was fulfilled, when it can be judged from the match result
+    fn objective(&self, _participant: &ParticipantResult, _result: &MatchResult) -> Option<bool> {
+        None
+    }
+
+    /// Game time, in seconds, at which the class expects its next update
+    fn next_deadline(&self) -> Option<u64> {
+        None
+    }
 }
 
 impl PlayerClass {
@@ -88,18 +112,38 @@ impl PlayerClass {
     pub fn get_state(&self) -> PlayerState {
         self.inner().state()
     }
+
+    pub fn receive_result(
+        &self,
+        participant: &ParticipantResult,
+        result: &MatchResult,
+        player: &Player,
+    ) -> Result<(), Error> {
+        self.inner().receive_result(participant, result, player)
+    }
+
+    pub fn objective(&self, participant: &ParticipantResult, result: &MatchResult) -> Option<bool> {
+        self.inner().objective(participant, result)
+    }
+
+    pub fn next_deadline(&self) -> Option<u64> {
+        self.inner().next_deadline()
+    }
 }
 
-impl From<Role> for PlayerClass {
-    fn from(value: Role) -> Self {
-        match value {
+impl PlayerClass {
+    /// Creates the class of a role
+    ///
+    /// - rng: Generator of the random events of the class, drawn from the one of the game
+    pub fn new(role: Role, rng: GameRng) -> Self {
+        match role {
             Role::SuperHero => PlayerClass::SuperHero(Default::default()),
             Role::Impostor => PlayerClass::Impostor(Default::default()),
             Role::Crook => PlayerClass::Crook(Default::default()),
             Role::Kamikaze => PlayerClass::Kamikaze(Default::default()),
-            Role::Romeo => PlayerClass::Romeo(Default::default()),
-            Role::TwoFace => PlayerClass::TwoFace(Default::default()),
-            Role::Droid => PlayerClass::Droid(Default::default()),
+            Role::Romeo => PlayerClass::Romeo(Romeo::new(rng)),
+            Role::TwoFace => PlayerClass::TwoFace(TwoFace::new(rng)),
+            Role::Droid => PlayerClass::Droid(Droid::new(rng)),
         }
     }
 }
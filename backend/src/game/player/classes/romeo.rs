@@ -1,11 +1,11 @@
 use std::sync::Mutex;
 
 use num_derive::FromPrimitive;
-use rand::{seq::IteratorRandom, thread_rng};
+use rand::seq::IteratorRandom;
 use serde::Serialize;
 
 use crate::{
-    game::{messages::Message, GameInfo, GameInfoMutation},
+    game::{messages::Message, GameInfo, GameInfoMutation, GameRng},
     routes::error::Error,
 };
 
@@ -50,9 +50,19 @@ struct State {
     juliette: Option<Juliette>,
 }
 
-#[derive(Default, Debug)]
+#[derive(Debug)]
 pub struct Romeo {
     state: Mutex<State>,
+    rng: Mutex<GameRng>,
+}
+
+impl Romeo {
+    pub fn new(rng: GameRng) -> Self {
+        Self {
+            state: Default::default(),
+            rng: Mutex::new(rng),
+        }
+    }
 }
 
 impl Class for Romeo {
@@ -61,18 +71,17 @@ impl Class for Romeo {
         _game_data: &GameInfo,
         player: &crate::game::player::Player,
     ) -> Result<(), crate::routes::error::Error> {
-        let juliette = *POSITIONS
-            .iter()
-            .choose(&mut thread_rng())
-            .ok_or(Error::Internal {
-                msg: "rand error".into(),
-            })?;
+        let mut rng = self.rng.lock().unwrap();
+
+        let juliette = *POSITIONS.iter().choose(&mut *rng).ok_or(Error::Internal {
+            msg: "rand error".into(),
+        })?;
         let juliette = Juliette {
             juliette,
             substitute: *POSITIONS
                 .iter()
                 .filter(|p| p != &&juliette)
-                .choose(&mut thread_rng())
+                .choose(&mut *rng)
                 .ok_or(Error::Internal {
                     msg: "rand error".into(),
                 })?,
@@ -1,6 +1,9 @@
 use serde::Serialize;
 
-use crate::game::{GameInfo, GameInfoMutation};
+use crate::game::{
+    match_result::{MatchResult, ParticipantResult},
+    GameInfo, GameInfoMutation,
+};
 
 use super::Class;
 
@@ -27,6 +30,10 @@ impl Class for SuperHero {
     fn state(&self) -> super::PlayerState {
         super::PlayerState::SuperHero(SuperHeroState)
     }
+
+    fn objective(&self, participant: &ParticipantResult, _result: &MatchResult) -> Option<bool> {
+        Some(participant.win)
+    }
 }
 
 #[derive(Debug, Serialize)]
@@ -1,10 +1,10 @@
 use std::sync::Mutex;
 
-use rand::{thread_rng, Rng};
+use rand::Rng;
 use rand_distr::{Distribution, Uniform};
 use serde::Serialize;
 
-use crate::game::{messages::Message, GameInfo, GameInfoMutation};
+use crate::game::{messages::Message, GameInfo, GameInfoMutation, GameRng};
 
 use super::Class;
 
@@ -14,9 +14,19 @@ struct State {
     next_swap_time: f64,
 }
 
-#[derive(Default, Debug)]
+#[derive(Debug)]
 pub struct TwoFace {
     state: Mutex<State>,
+    rng: Mutex<GameRng>,
+}
+
+impl TwoFace {
+    pub fn new(rng: GameRng) -> Self {
+        Self {
+            state: Default::default(),
+            rng: Mutex::new(rng),
+        }
+    }
 }
 
 impl Class for TwoFace {
@@ -26,9 +36,10 @@ impl Class for TwoFace {
         player: &crate::game::player::Player,
     ) -> Result<(), crate::routes::error::Error> {
         let mut lock = self.state.lock().unwrap();
+        let mut rng = self.rng.lock().unwrap();
 
-        lock.inting = thread_rng().gen();
-        lock.next_swap_time = Uniform::new(300.0, 600.0).sample(&mut thread_rng());
+        lock.inting = rng.gen();
+        lock.next_swap_time = Uniform::new(300.0, 600.0).sample(&mut *rng);
 
         player.proxy.send_message(Message::TwoFaceState {
             inting: lock.inting,
@@ -49,9 +60,16 @@ impl Class for TwoFace {
         if let GameInfoMutation::Duration((_, new_time)) = mutation {
             if lock.next_swap_time <= *new_time as f64 {
                 lock.inting = !lock.inting;
-                lock.next_swap_time += Uniform::new(300.0, 600.0).sample(&mut thread_rng());
+                lock.next_swap_time +=
+                    Uniform::new(300.0, 600.0).sample(&mut *self.rng.lock().unwrap());
 
-                println!("Assigning new inting: {} at {:?}", lock.inting, new_time);
+                tracing::debug!(
+                    player = %player.session.name,
+                    inting = lock.inting,
+                    game_time = new_time,
+                    next_swap = lock.next_swap_time,
+                    "Swapping TwoFace state"
+                );
 
                 player.proxy.send_message(Message::TwoFaceState {
                     inting: lock.inting,
@@ -61,6 +79,11 @@ impl Class for TwoFace {
         Ok(())
     }
 
+    fn next_deadline(&self) -> Option<u64> {
+        let next = self.state.lock().unwrap().next_swap_time;
+        Some(next.max(0.0).ceil() as u64)
+    }
+
     fn state(&self) -> super::PlayerState {
         super::PlayerState::TwoFace(TwoFaceState {
             inting: self.state.lock().unwrap().inting,
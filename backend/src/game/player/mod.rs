@@ -1,12 +1,21 @@
-use crate::{routes::error::Error, session_management::UserSession};
+use serde::Serialize;
+use uuid::Uuid;
+
+use crate::{
+    models::{MergedPlayerData, Team},
+    routes::error::Error,
+    session_management::UserSession,
+};
 
 use self::{
+    bot::Bot,
     classes::{PlayerClass, PlayerState},
     proxy::PlayerProxy,
 };
 
-use super::{team_builder::Role, GameInfo, GameInfoMutation};
+use super::{match_result::MatchResult, team_builder::Role, GameInfo, GameInfoMutation, GameRng};
 
+pub mod bot;
 pub mod classes;
 pub mod proxy;
 
@@ -15,6 +24,32 @@ pub struct Player {
     class: Option<PlayerClass>,
     pub proxy: PlayerProxy,
     pub session: UserSession,
+    /// Summoner played by this player in the League game
+    pub binding: Option<SummonerBinding>,
+    /// Set for the players controlled by the server
+    pub bot: Option<Bot>,
+    /// Side of the League game played by the player, in the lobbies playing both teams
+    pub team: Option<Team>,
+}
+
+/// Summoner of the League game played by a lobby player
+#[derive(Debug, Clone, Serialize)]
+pub struct SummonerBinding {
+    pub summoner_name: String,
+    /// Unknown until live data is received
+    pub champion_name: Option<String>,
+    /// Lane of the summoner, as reported by the live data
+    pub position: Option<String>,
+}
+
+impl From<&MergedPlayerData> for SummonerBinding {
+    fn from(value: &MergedPlayerData) -> Self {
+        Self {
+            summoner_name: value.summoner_name.clone(),
+            champion_name: Some(value.champion_name.clone()),
+            position: Some(value.position.clone()).filter(|p| !p.is_empty()),
+        }
+    }
 }
 
 impl Player {
@@ -23,6 +58,27 @@ impl Player {
             class: Default::default(),
             proxy,
             session,
+            binding: None,
+            bot: None,
+            team: None,
+        }
+    }
+
+    /// Creates a player controlled by the server, without any login session
+    pub fn new_bot(name: String, bot: Bot) -> Self {
+        Self {
+            class: Default::default(),
+            proxy: PlayerProxy::new(name.clone()),
+            session: UserSession {
+                name,
+                account: None,
+                sid: Uuid::nil(),
+                reservation: Uuid::nil(),
+                auth_time: 0,
+            },
+            binding: None,
+            bot: Some(bot),
+            team: None,
         }
     }
 
@@ -32,28 +88,60 @@ impl Player {
         game_data: &GameInfo,
     ) -> Result<(), Error> {
         if let Some(ref class) = self.class {
-            class.receive_mutation(&mutation, game_data, self)?;
+            class.receive_mutation(mutation, game_data, self)?;
         }
 
         Ok(())
     }
 
+    /// Game time, in seconds, at which the class of the player expects its next update
+    pub fn next_deadline(&self) -> Option<u64> {
+        self.class.as_ref().and_then(PlayerClass::next_deadline)
+    }
+
     pub fn state(&self) -> Option<PlayerState> {
         self.class.as_ref().map(|c| c.get_state())
     }
 
-    pub fn set_role(&mut self, role: Role) -> Result<(), Error> {
+    /// Assigns a role to the player
+    ///
+    /// - rng: Generator used by the class for its random events
+    pub fn set_role(&mut self, role: Role, rng: GameRng) -> Result<(), Error> {
         if self.class.is_some() {
             return Err(Error::AlreadyStarted);
         }
 
-        self.class = Some(PlayerClass::from(role));
+        self.class = Some(PlayerClass::new(role, rng));
         self.proxy
-            .send_message(super::messages::Message::Role { role: role });
+            .send_message(super::messages::Message::Role { role });
 
         Ok(())
     }
 
+    /// Removes the role of the player, so that a new one can be assigned
+    pub fn reset_role(&mut self) {
+        self.class = None;
+    }
+
+    /// Notifies the class of the player that the match result is available
+    pub fn receive_result(&self, result: &MatchResult) -> Result<(), Error> {
+        match (&self.class, result.participant(self)) {
+            (Some(class), Some(participant)) => class.receive_result(participant, result, self),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the name of the summoner played by this player, if known
+    pub fn summoner_name(&self) -> Option<&str> {
+        self.binding.as_ref().map(|b| b.summoner_name.as_str())
+    }
+
+    /// Whether the player fulfilled the objective of their role, if it can be judged from the result
+    pub fn objective(&self, result: &MatchResult) -> Option<bool> {
+        let participant = result.participant(self)?;
+        self.class.as_ref()?.objective(participant, result)
+    }
+
     pub fn role(&self) -> Option<Role> {
         self.class.as_ref().map(|p| match p {
             PlayerClass::SuperHero(_) => Role::SuperHero,
@@ -3,19 +3,22 @@ use std::sync::{Arc, Mutex};
 
 #[derive(Debug, Clone)]
 pub struct PlayerProxy {
+    /// Name of the player receiving the messages
+    pub name: String,
     pub messages: Arc<Mutex<Vec<Message>>>,
 }
 
 impl PlayerProxy {
-    pub fn send_message(&self, msg: Message) {
-        self.messages.as_ref().lock().unwrap().push(msg)
-    }
-}
-
-impl Default for PlayerProxy {
-    fn default() -> Self {
+    pub fn new(name: String) -> Self {
         Self {
+            name,
             messages: Arc::new(Mutex::new(vec![Message::Hi])),
         }
     }
+
+    pub fn send_message(&self, msg: Message) {
+        // The messages reveal the roles, which must not end up in the logs
+        tracing::debug!(recipient = %self.name, "Sending message");
+        self.messages.as_ref().lock().unwrap().push(msg)
+    }
 }
@@ -1,4 +1,4 @@
-use rand::seq::IteratorRandom;
+use rand::{seq::IteratorRandom, Rng};
 use serde::{Serialize, Deserialize};
 use Role::*;
 
@@ -14,29 +14,34 @@ pub enum Role {
     Droid,
 }
 
+impl Role {
+    pub const ALL: [Role; 7] = [SuperHero, Impostor, Crook, Kamikaze, Romeo, TwoFace, Droid];
+}
+
 /// Generates a random composition for a game
 ///
 /// The composition will contain:
 /// - One SuperHero
-/// - One Impostor and or one Crook
-/// - Two to three other roles, to fill up to five roles
-pub fn generate_composition() -> Vec<Role> {
-    let mut roles = vec![SuperHero, Droid, TwoFace];
+/// - One Impostor or one Crook, or both from five players
+/// - The Droid and the TwoFace, as long as there are players left
+/// - Kamikaze or Romeo, to fill up to `player_count` roles
+///
+/// Roles are returned in a fixed order, they must be shuffled before being assigned. For five players, the random draws
+/// and the order are those of the former five-player composition, so that the seeds of older games still reproduce them.
+pub fn generate_composition(rng: &mut impl Rng, player_count: usize) -> Vec<Role> {
+    let traitors: &[Role] = match rng.gen_range(0..if player_count >= 5 { 3 } else { 2 }) {
+        0 => &[Impostor],
+        1 => &[Crook],
+        _ => &[Impostor, Crook],
+    };
 
-    match rand::random::<u8>() % 3 {
-        0 => roles.push(Impostor),
-        1 => roles.push(Crook),
-        _ => {
-            roles.push(Impostor);
-            roles.push(Crook)
-        }
-    }
+    let mut roles = vec![SuperHero];
+    let missing = player_count.saturating_sub(roles.len() + traitors.len());
+    roles.extend([Droid, TwoFace].into_iter().choose_multiple(rng, missing));
+    roles.extend_from_slice(traitors);
 
-    let other_roles = vec![Kamikaze, Romeo];
-    let slice = other_roles
-        .iter()
-        .choose_multiple(&mut rand::thread_rng(), 5 - roles.len());
-    slice.iter().for_each(|r| roles.push(**r));
+    let missing = player_count.saturating_sub(roles.len());
+    roles.extend([Kamikaze, Romeo].into_iter().choose_multiple(rng, missing));
 
     roles
 }
@@ -0,0 +1,152 @@
+//! Keys used to sign and verify the session tokens
+//!
+//! Several keys can be configured at once, identified by the `kid` header of the tokens, so that a new key can be
+//! introduced without invalidating the tokens signed by the previous ones.
+
+use std::{str::FromStr, sync::OnceLock};
+
+use jsonwebtoken::{
+    errors::{ErrorKind, Result},
+    Algorithm, DecodingKey, EncodingKey, Header, TokenData, Validation,
+};
+use serde::{de::DeserializeOwned, Serialize};
+
+use crate::env::{env_config, Config};
+
+/// A key able to verify tokens, and possibly to sign them
+pub struct Key {
+    /// Id of the key, `None` for the legacy JWT_SECRET
+    kid: Option<String>,
+    algorithm: Algorithm,
+    encoding: Option<EncodingKey>,
+    decoding: DecodingKey,
+}
+
+pub struct Keyring {
+    keys: Vec<Key>,
+    /// Index of the key signing new tokens
+    signing: usize,
+}
+
+impl Key {
+    fn from_secret(kid: Option<String>, secret: &str) -> Self {
+        Self {
+            kid,
+            algorithm: Algorithm::HS256,
+            encoding: Some(EncodingKey::from_secret(secret.as_bytes())),
+            decoding: DecodingKey::from_secret(secret.as_bytes()),
+        }
+    }
+
+    /// Loads a key described as `kid=ALG:public.pem[:private.pem]`
+    fn from_files(description: &str) -> std::result::Result<Self, String> {
+        let (kid, description) = description
+            .split_once('=')
+            .ok_or_else(|| format!("missing key id in {description}"))?;
+        let mut parts = description.split(':');
+
+        let algorithm = Algorithm::from_str(parts.next().unwrap_or_default())
+            .map_err(|e| format!("{kid}: {e}"))?;
+        let read = |path: &str| std::fs::read(path).map_err(|e| format!("{kid}: {path}: {e}"));
+        let public = read(
+            parts
+                .next()
+                .ok_or_else(|| format!("{kid}: missing public key"))?,
+        )?;
+        let private = parts.next().map(read).transpose()?;
+
+        let (decoding, encoding) = match algorithm {
+            Algorithm::EdDSA => (
+                DecodingKey::from_ed_pem(&public),
+                private.map(|p| EncodingKey::from_ed_pem(&p)).transpose(),
+            ),
+            Algorithm::RS256 => (
+                DecodingKey::from_rsa_pem(&public),
+                private.map(|p| EncodingKey::from_rsa_pem(&p)).transpose(),
+            ),
+            _ => {
+                return Err(format!(
+                    "{kid}: only EdDSA and RS256 keys can be loaded from files"
+                ))
+            }
+        };
+
+        Ok(Self {
+            kid: Some(kid.to_owned()),
+            algorithm,
+            encoding: encoding.map_err(|e| format!("{kid}: {e}"))?,
+            decoding: decoding.map_err(|e| format!("{kid}: {e}"))?,
+        })
+    }
+}
+
+impl Keyring {
+    /// Loads all the keys from the configuration
+    ///
+    /// The signing key is either the one designated by JWT_SIGNING_KEY, or the last configured one able to sign
+    pub fn load(config: &Config) -> std::result::Result<Self, String> {
+        let mut keys = Vec::new();
+
+        if let Some(ref secret) = config.jwt_secret {
+            keys.push(Key::from_secret(None, secret));
+        }
+
+        for description in config.jwt_keys.split(',').filter(|s| !s.is_empty()) {
+            let (kid, secret) = description
+                .split_once('=')
+                .ok_or("missing key id in a JWT_KEYS entry")?;
+            keys.push(Key::from_secret(Some(kid.to_owned()), secret));
+        }
+
+        for description in config.jwt_key_files.split(',').filter(|s| !s.is_empty()) {
+            keys.push(Key::from_files(description)?);
+        }
+
+        let signing = match config.jwt_signing_key {
+            Some(ref kid) => keys
+                .iter()
+                .position(|k| k.kid.as_ref() == Some(kid) && k.encoding.is_some())
+                .ok_or_else(|| format!("signing key {kid} not found or missing its private key"))?,
+            None => keys
+                .iter()
+                .rposition(|k| k.encoding.is_some())
+                .ok_or("no key able to sign tokens is configured")?,
+        };
+
+        Ok(Self { keys, signing })
+    }
+
+    /// Encodes claims with the signing key
+    pub fn encode<T: Serialize>(&self, claims: &T) -> Result<String> {
+        let key = &self.keys[self.signing];
+
+        let mut header = Header::new(key.algorithm);
+        header.kid = key.kid.clone();
+
+        jsonwebtoken::encode(
+            &header,
+            claims,
+            key.encoding
+                .as_ref()
+                .expect("signing key without private key"),
+        )
+    }
+
+    /// Decodes a token with the key designated by its header, validating its expiration
+    pub fn decode<T: DeserializeOwned>(&self, token: &str) -> Result<TokenData<T>> {
+        let header = jsonwebtoken::decode_header(token)?;
+        let key = self
+            .keys
+            .iter()
+            .find(|k| k.kid == header.kid)
+            .ok_or(ErrorKind::InvalidSignature)?;
+
+        jsonwebtoken::decode(token, &key.decoding, &Validation::new(key.algorithm))
+    }
+}
+
+pub static KEYRING: OnceLock<Keyring> = OnceLock::new();
+
+pub fn keyring() -> &'static Keyring {
+    KEYRING.get_or_init(|| Keyring::load(env_config()).unwrap())
+}
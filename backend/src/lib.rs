@@ -0,0 +1,74 @@
+#![allow(dead_code)] // TODO
+
+use env::env_config;
+use rocket::{Build, Rocket};
+use std::sync::Arc;
+
+pub mod env;
+pub mod game;
+pub mod keyring;
+pub mod logging;
+pub mod lol_api;
+pub mod metrics;
+pub mod models;
+pub mod routes;
+pub mod scheduler;
+pub mod session_management;
+pub mod state;
+
+pub type AppState = rocket::State<Arc<state::State>>;
+
+/// Builds the server and spawns the background tasks processing its games
+///
+/// Must be called from within a Tokio runtime. Also used by the tests, to run the server in-process.
+pub fn rocket() -> Rocket<Build> {
+    let scheduler = scheduler::Scheduler::default();
+    let state = Arc::new(state::State::new(scheduler.clone()));
+    tokio::spawn(state::State::janitor(Arc::downgrade(&state)));
+    tokio::spawn(scheduler.run(Arc::downgrade(&state)));
+
+    rocket::build()
+        .manage(state)
+        .attach(metrics::RequestMetrics)
+        .mount(
+            "/",
+            rocket::routes![
+                metrics::metrics,
+                routes::health::health,
+                routes::health::ready
+            ],
+        )
+        .mount(
+            &env_config().uri,
+            rocket::routes![
+                routes::get_updates,
+                routes::login,
+                routes::refresh,
+                routes::logout,
+                routes::account::link_account,
+                routes::account::verify_account,
+                routes::game::get_game,
+                routes::game::get_game_authenticated,
+                routes::game::get_current_game_authenticated,
+                routes::game::create_game,
+                routes::game::join_game,
+                routes::game::quit_game,
+                routes::game::start_game,
+                routes::game::end_game,
+                routes::game::post_votes,
+                routes::game::post_live_data,
+                routes::game::bind_summoner,
+                routes::game::set_team,
+                routes::game::add_bot,
+                routes::game::remove_bot,
+                routes::admin::list_games,
+                routes::admin::create_game,
+                routes::admin::force_state,
+                routes::admin::set_clock,
+                routes::admin::delete_game,
+                routes::admin::peek_messages,
+                routes::admin::remove_stale_proxies,
+            ],
+        )
+        .register(&env_config().uri, rocket::catchers![routes::unauthorized])
+}
@@ -0,0 +1,28 @@
+//! Installation of the tracing subscriber
+//!
+//! Events are written to stdout, either human readable or as one JSON object per line, and filtered by LOG_LEVEL.
+//! Rocket logs through the `log` crate, whose records are forwarded to the same subscriber.
+
+use tracing_subscriber::{fmt, prelude::*, EnvFilter};
+
+use crate::env::Config;
+
+/// Installs the global subscriber, panicking if the configuration is invalid
+pub fn init(config: &Config) {
+    let filter = EnvFilter::try_new(&config.log_level)
+        .unwrap_or_else(|e| panic!("invalid LOG_LEVEL {}: {e}", config.log_level));
+    let registry = tracing_subscriber::registry().with(filter);
+
+    match config.log_format.as_str() {
+        "pretty" => registry.with(fmt::layer().pretty()).init(),
+        "json" => registry
+            .with(
+                fmt::layer()
+                    .json()
+                    .with_current_span(true)
+                    .with_span_list(true),
+            )
+            .init(),
+        format => panic!("invalid LOG_FORMAT {format}, expected pretty or json"),
+    }
+}
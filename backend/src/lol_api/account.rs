@@ -19,3 +19,12 @@ pub async fn get_by_puuid(puuid: String) -> Result<SummonerByPuuid, Error> {
     )
     .await
 }
+
+pub async fn get_by_riot_id(game_name: &str, tag_line: &str) -> Result<SummonerByPuuid, Error> {
+    make_api_call(
+        format!("/riot/account/v1/accounts/by-riot-id/{game_name}/{tag_line}"),
+        &[],
+        true,
+    )
+    .await
+}
@@ -0,0 +1,78 @@
+use serde::Deserialize;
+
+use crate::routes::error::Error;
+
+use super::make_api_call;
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MatchDto {
+    /// Match metadata
+    pub metadata: MetadataDto,
+    /// Match info
+    pub info: InfoDto,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct MetadataDto {
+    /// Match id
+    pub match_id: String,
+    /// A list of participant PUUIDs
+    pub participants: Vec<String>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct InfoDto {
+    /// The ID of the game
+    pub game_id: i64,
+    /// Duration of the game, in seconds
+    pub game_duration: i64,
+    /// Unix timestamp for when match ends on the game server, in milliseconds
+    pub game_end_timestamp: Option<i64>,
+    /// The participant information
+    pub participants: Vec<ParticipantDto>,
+    /// The teams information
+    pub teams: Vec<TeamDto>,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct ParticipantDto {
+    /// Encrypted PUUID of the participant
+    pub puuid: String,
+    /// Encrypted summoner ID of the participant
+    pub summoner_id: String,
+    /// Summoner name of the participant
+    pub summoner_name: String,
+    /// Name of the champion played
+    pub champion_name: String,
+    /// The team ID of this participant (100 or 200)
+    pub team_id: i64,
+    /// Position of the participant (TOP, JUNGLE, MIDDLE, BOTTOM or UTILITY)
+    pub team_position: String,
+    pub kills: i64,
+    pub deaths: i64,
+    pub assists: i64,
+    /// Total damage dealt to enemy champions
+    pub total_damage_dealt_to_champions: i64,
+    /// Whether the participant's team won
+    pub win: bool,
+}
+
+#[derive(Debug, Deserialize, Clone)]
+#[serde(rename_all = "camelCase")]
+pub struct TeamDto {
+    /// The ID of the team (100 or 200)
+    pub team_id: i64,
+    /// Whether the team won
+    pub win: bool,
+}
+
+/// Returns the match with the given id, formatted as `{platformId}_{gameId}`
+///
+/// Only finished games are available, [Error::NotFound] is returned otherwise
+pub async fn get_match(match_id: &str) -> Result<MatchDto, Error> {
+    make_api_call(format!("/lol/match/v5/matches/{match_id}"), &[], true).await
+}
@@ -1,9 +1,15 @@
 use serde::{de::DeserializeOwned, Deserialize};
 
-use crate::{env::env_config, routes::error::Error};
+use crate::{
+    env::env_config,
+    metrics::{RIOT_API_CALLS, RIOT_API_RATE_LIMITED},
+    routes::error::Error,
+};
 
 pub mod account;
+pub mod matches;
 pub mod spectator;
+pub mod status;
 pub mod summoners;
 
 #[derive(Deserialize, Debug)]
@@ -27,7 +33,11 @@ where
         Error { status: LolApiError },
     }
 
-    let result: ApiResult<T> = reqwest::Client::new()
+    let Some(api_key) = env_config().lol_api_key.as_ref() else {
+        return Err(Error::LolApiDisabled);
+    };
+
+    let response = reqwest::Client::new()
         .get(format!(
             "https://{}.api.riotgames.com{}",
             if region {
@@ -37,15 +47,23 @@ where
             },
             uri
         ))
-        .header("X-Riot-Token", env_config().lol_api_key.as_str())
+        .header("X-Riot-Token", api_key.as_str())
         .query(parameters)
         .send()
-        .await?
-        .json()
         .await?;
 
+    RIOT_API_CALLS
+        .with_label_values(&[response.status().as_str()])
+        .inc();
+    if response.status() == reqwest::StatusCode::TOO_MANY_REQUESTS {
+        RIOT_API_RATE_LIMITED.inc();
+    }
+
+    let result: ApiResult<T> = response.json().await?;
+
     match result {
         ApiResult::Value(v) => Ok(v),
+        ApiResult::Error { status } if status.status_code == 404 => Err(Error::NotFound),
         ApiResult::Error { status } => Err(Error::from(status)),
     }
 }
@@ -0,0 +1,50 @@
+use std::{
+    sync::Mutex,
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+
+use crate::routes::error::Error;
+
+use super::make_api_call;
+
+/// Time during which the result of a reachability check is reused, to spare the rate limit of the API key
+const REACHABILITY_CACHE: Duration = Duration::from_secs(60);
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlatformData {
+    /// The ID of the platform, e.g. EUW1
+    pub id: String,
+    pub name: String,
+}
+
+pub async fn get_platform_data() -> Result<PlatformData, Error> {
+    make_api_call("/lol/status/v4/platform-data".to_owned(), &[], false).await
+}
+
+/// Checks that the Riot API answers with the configured key, reusing the last result for a minute
+///
+/// Returns [Error::LolApiDisabled] when no key is configured.
+pub async fn is_reachable() -> Result<bool, Error> {
+    static LAST_CHECK: Mutex<Option<(Instant, bool)>> = Mutex::new(None);
+
+    if let Some((at, reachable)) = *LAST_CHECK.lock().unwrap() {
+        if at.elapsed() < REACHABILITY_CACHE {
+            return Ok(reachable);
+        }
+    }
+
+    let reachable = match get_platform_data().await {
+        Ok(_) => true,
+        Err(Error::LolApiDisabled) => return Err(Error::LolApiDisabled),
+        Err(e) => {
+            tracing::warn!("Riot API is unreachable: {:?}", e);
+            false
+        }
+    };
+
+    *LAST_CHECK.lock().unwrap() = Some((Instant::now(), reachable));
+    Ok(reachable)
+}
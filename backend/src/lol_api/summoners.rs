@@ -30,3 +30,12 @@ pub async fn get_by_name(name: String) -> Result<SummonerByName, Error> {
     )
     .await
 }
+
+pub async fn get_by_puuid(puuid: &str) -> Result<SummonerByName, Error> {
+    make_api_call(
+        format!("/lol/summoner/v4/summoners/by-puuid/{puuid}"),
+        &[],
+        false,
+    )
+    .await
+}
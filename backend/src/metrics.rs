@@ -0,0 +1,179 @@
+//! Prometheus metrics, exposed on `/metrics`
+//!
+//! Counters and histograms are updated as events happen, while the gauges describing the state are computed on each
+//! scrape.
+
+use std::time::{Duration, Instant};
+
+use lazy_static::lazy_static;
+use prometheus::{
+    register_histogram_vec, register_int_counter, register_int_counter_vec, register_int_gauge,
+    register_int_gauge_vec, Encoder, HistogramVec, IntCounter, IntCounterVec, IntGauge,
+    IntGaugeVec, TextEncoder,
+};
+use rocket::{
+    fairing::{Fairing, Info, Kind},
+    get,
+    http::ContentType,
+    Data, Request, Response,
+};
+use tracing::instrument;
+
+use crate::{game::StateKind, routes::error::Error, state::State, AppState};
+
+/// Players who made a request within this window are considered online
+const ONLINE_WINDOW: Duration = Duration::from_secs(300);
+
+lazy_static! {
+    pub static ref HTTP_REQUEST_DURATION: HistogramVec = register_histogram_vec!(
+        "http_request_duration_seconds",
+        "Latency of the requests, by route",
+        &["method", "route", "status"]
+    )
+    .unwrap();
+    pub static ref ERRORS: IntCounterVec = register_int_counter_vec!(
+        "errors_total",
+        "Errors returned to the clients, by variant",
+        &["error"]
+    )
+    .unwrap();
+    pub static ref RIOT_API_CALLS: IntCounterVec = register_int_counter_vec!(
+        "riot_api_calls_total",
+        "Calls to the Riot API, by response status",
+        &["status"]
+    )
+    .unwrap();
+    pub static ref RIOT_API_RATE_LIMITED: IntCounter = register_int_counter!(
+        "riot_api_rate_limited_total",
+        "Calls to the Riot API rejected by its rate limit"
+    )
+    .unwrap();
+    static ref GAMES: IntGaugeVec =
+        register_int_gauge_vec!("games", "Games, by state", &["state"]).unwrap();
+    static ref PLAYERS_ONLINE: IntGauge = register_int_gauge!(
+        "players_online",
+        "Players who made a request within the last five minutes"
+    )
+    .unwrap();
+    static ref PLAYERS_IN_GAME: IntGauge =
+        register_int_gauge!("players_in_game", "Players in a game").unwrap();
+    static ref PROXIES: IntGauge =
+        register_int_gauge!("proxies", "Message queues of the players").unwrap();
+    static ref QUEUED_MESSAGES: IntGauge = register_int_gauge!(
+        "queued_messages",
+        "Messages waiting to be fetched, in all the proxies"
+    )
+    .unwrap();
+    static ref MAX_QUEUED_MESSAGES: IntGauge = register_int_gauge!(
+        "max_queued_messages",
+        "Messages waiting to be fetched in the fullest proxy"
+    )
+    .unwrap();
+    static ref SCHEDULED_GAMES: IntGauge = register_int_gauge!(
+        "scheduled_games",
+        "Games waiting for a deadline in the scheduler"
+    )
+    .unwrap();
+}
+
+/// Counts an error returned to a client
+pub fn record_error(error: &Error) {
+    if let Ok(value) = serde_json::to_value(error) {
+        if let Some(name) = value["error"].as_str() {
+            ERRORS.with_label_values(&[name]).inc();
+        }
+    }
+}
+
+/// Computes the gauges describing the state
+async fn update_gauges(state: &State) {
+    let games = state
+        .games
+        .iter()
+        .map(|e| e.value().clone())
+        .collect::<Vec<_>>();
+
+    let mut counts = StateKind::ALL.map(|kind| (kind, 0));
+    for game in games {
+        let kind = game.read().await.state_kind();
+        if let Some((_, count)) = counts.iter_mut().find(|(k, _)| *k == kind) {
+            *count += 1;
+        }
+    }
+    for (kind, count) in counts {
+        GAMES.with_label_values(&[kind.as_str()]).set(count);
+    }
+
+    PLAYERS_ONLINE.set(
+        state
+            .names
+            .iter()
+            .filter(|r| r.last_seen.elapsed() < ONLINE_WINDOW)
+            .count() as i64,
+    );
+    PLAYERS_IN_GAME.set(state.players.len() as i64);
+
+    let queues = state
+        .messages
+        .iter()
+        .map(|p| p.messages.lock().unwrap().len() as i64)
+        .collect::<Vec<_>>();
+    PROXIES.set(queues.len() as i64);
+    QUEUED_MESSAGES.set(queues.iter().sum());
+    MAX_QUEUED_MESSAGES.set(queues.into_iter().max().unwrap_or_default());
+
+    SCHEDULED_GAMES.set(state.scheduler.pending() as i64);
+}
+
+#[get("/metrics")]
+#[instrument(skip_all)]
+pub async fn metrics(state: &AppState) -> Result<(ContentType, String), Error> {
+    update_gauges(state).await;
+
+    let encoder = TextEncoder::new();
+    let mut buffer = Vec::new();
+    encoder
+        .encode(&prometheus::gather(), &mut buffer)
+        .map_err(Error::from)?;
+
+    Ok((
+        ContentType::parse_flexible(encoder.format_type()).unwrap_or(ContentType::Plain),
+        String::from_utf8(buffer).map_err(Error::from)?,
+    ))
+}
+
+/// Start of a request, stored in its local cache
+struct RequestStart(Instant);
+
+/// Fairing measuring the latency of each route
+pub struct RequestMetrics;
+
+#[rocket::async_trait]
+impl Fairing for RequestMetrics {
+    fn info(&self) -> Info {
+        Info {
+            name: "Request metrics",
+            kind: Kind::Request | Kind::Response,
+        }
+    }
+
+    async fn on_request(&self, req: &mut Request<'_>, _: &mut Data<'_>) {
+        req.local_cache(|| RequestStart(Instant::now()));
+    }
+
+    async fn on_response<'r>(&self, req: &'r Request<'_>, res: &mut Response<'r>) {
+        let start = req.local_cache(|| RequestStart(Instant::now())).0;
+        let route = req
+            .route()
+            .map(|r| r.uri.to_string())
+            .unwrap_or_else(|| "unmatched".into());
+
+        HTTP_REQUEST_DURATION
+            .with_label_values(&[
+                req.method().as_str(),
+                &route,
+                &res.status().code.to_string(),
+            ])
+            .observe(start.elapsed().as_secs_f64());
+    }
+}
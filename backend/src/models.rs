@@ -1,7 +1,7 @@
 //! Module containing all the structures that can be deserialized from the `https://127.0.0.1:2999/liveclientdata/` endpoint.
 
 use mutable::{cmp::SoftEq, Mutable, SoftEq};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 
 #[derive(Deserialize, Debug, Clone, PartialEq, Mutable, Default)]
 pub struct MergedGameData {
@@ -41,6 +41,25 @@ impl From<AllGameData> for MergedGameData {
     }
 }
 
+impl MergedGameData {
+    /// Merges data previously uploaded by another player, keeping the active player data of both uploaders
+    pub fn merge(mut self, previous: &MergedGameData) -> Self {
+        for player in self
+            .all_players
+            .iter_mut()
+            .filter(|p| p.active_player.is_none())
+        {
+            player.active_player = previous
+                .all_players
+                .iter()
+                .find(|p| p.summoner_name == player.summoner_name)
+                .and_then(|p| p.active_player.clone());
+        }
+
+        self
+    }
+}
+
 #[derive(Deserialize, Debug, Clone, PartialEq, Mutable, SoftEq)]
 pub struct MergedPlayerData {
     pub active_player: Option<ActivePlayer>,
@@ -266,7 +285,11 @@ pub struct Events {
 }
 
 #[derive(Deserialize, Debug, Clone, PartialEq, Mutable)]
-#[serde(tag = "EventName", rename_all = "PascalCase")]
+#[serde(
+    tag = "EventName",
+    rename_all = "PascalCase",
+    rename_all_fields = "PascalCase"
+)]
 pub enum EventData {
     GameStart,
     GameEnd {
@@ -327,7 +350,8 @@ pub enum EventData {
     },
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq, Mutable)]
+#[derive(Deserialize, Serialize, Debug, Clone, PartialEq, Mutable)]
+#[serde(rename_all(serialize = "snake_case"))]
 pub enum GameResult {
     Win,
     Loss,
@@ -352,9 +376,60 @@ pub enum GameMode {
     Aram,
 }
 
-#[derive(Deserialize, Debug, Clone, PartialEq, Mutable)]
+#[derive(Deserialize, Serialize, Debug, Clone, Copy, PartialEq, Eq, Hash, Mutable)]
 #[serde(rename_all = "UPPERCASE")]
 pub enum Team {
+    /// Blue side
     Order,
+    /// Red side
     Chaos,
 }
+
+impl Team {
+    pub fn opponent(self) -> Self {
+        match self {
+            Team::Order => Team::Chaos,
+            Team::Chaos => Team::Order,
+        }
+    }
+
+    /// Id of the team in the Riot API
+    pub fn team_id(self) -> i64 {
+        match self {
+            Team::Order => 100,
+            Team::Chaos => 200,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn event_fields_are_read_in_pascal_case() {
+        let events: Events = serde_json::from_str(
+            r#"{"Events": [
+                {"EventID": 0, "EventName": "GameStart", "EventTime": 0.02},
+                {"EventID": 1, "EventName": "ChampionKill", "EventTime": 95.4, "KillerName": "a", "VictimName": "b", "Assisters": ["c"]},
+                {"EventID": 2, "EventName": "GameEnd", "EventTime": 1200.0, "Result": "Win"}
+            ]}"#,
+        )
+        .unwrap();
+
+        assert_eq!(
+            events.events[1].data,
+            EventData::ChampionKill {
+                killer_name: "a".into(),
+                victim_name: "b".into(),
+                assisters: vec!["c".into()],
+            }
+        );
+        assert_eq!(
+            events.events[2].data,
+            EventData::GameEnd {
+                result: GameResult::Win
+            }
+        );
+    }
+}
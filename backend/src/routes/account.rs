@@ -0,0 +1,114 @@
+use std::time::{Duration, Instant};
+
+use rand::{thread_rng, Rng};
+use rocket::{http::CookieJar, post, serde::json::Json};
+use serde::{Deserialize, Serialize};
+use tracing::instrument;
+
+use crate::{
+    lol_api::{account, summoners},
+    session_management::{LinkChallenge, LinkedAccount, UserSession},
+    AppState,
+};
+
+use super::{error::Error, LoginResponse};
+
+/// Time given to a player to change their profile icon
+const CHALLENGE_DURATION: Duration = Duration::from_secs(300);
+
+/// Profile icons owned by every account, usable for the challenge
+const FREE_ICONS: std::ops::RangeInclusive<i32> = 0..=28;
+
+#[derive(Debug, Deserialize)]
+pub struct LinkForm {
+    /// Riot ID, formatted as gameName#tagLine
+    riot_id: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LinkResponse {
+    /// Profile icon to set on the account to prove its ownership
+    icon_id: i32,
+    /// Seconds left to complete the challenge
+    expires_in: u64,
+}
+
+/// Starts linking a League account to the session
+///
+/// The player must then set the returned profile icon and call `/account/verify`
+#[post("/account/link", format = "json", data = "<form>")]
+#[instrument(skip_all, fields(player = %session.name))]
+pub async fn link_account(
+    session: UserSession,
+    state: &AppState,
+    form: Json<LinkForm>,
+) -> Result<Json<LinkResponse>, Error> {
+    let (game_name, tag_line) = form
+        .riot_id
+        .rsplit_once('#')
+        .filter(|(name, tag)| !name.is_empty() && !tag.is_empty())
+        .ok_or(Error::InvalidRiotId)?;
+
+    let riot_account = account::get_by_riot_id(game_name, tag_line).await?;
+    let summoner = summoners::get_by_puuid(&riot_account.puuid).await?;
+
+    let icon_id = loop {
+        let icon_id = thread_rng().gen_range(FREE_ICONS);
+        if icon_id != summoner.profile_icon_id {
+            break icon_id;
+        }
+    };
+
+    let now = Instant::now();
+
+    state.link_challenges.retain(|_, c| c.expires > now);
+    state.link_challenges.insert(
+        session.name,
+        LinkChallenge {
+            account: LinkedAccount {
+                puuid: riot_account.puuid,
+                summoner_id: summoner.id,
+                game_name: riot_account.game_name,
+                tag_line: riot_account.tag_line,
+            },
+            icon_id,
+            expires: now + CHALLENGE_DURATION,
+        },
+    );
+
+    Ok(Json(LinkResponse {
+        icon_id,
+        expires_in: CHALLENGE_DURATION.as_secs(),
+    }))
+}
+
+/// Completes the account linking challenge, and returns a new token containing the linked account
+#[post("/account/verify")]
+#[instrument(skip_all, fields(player = %session.name))]
+pub async fn verify_account(
+    session: UserSession,
+    state: &AppState,
+    cookies: &CookieJar<'_>,
+) -> Result<Json<LoginResponse>, Error> {
+    let challenge = state
+        .link_challenges
+        .get(&session.name)
+        .map(|c| c.clone())
+        .filter(|c| c.expires > Instant::now())
+        .ok_or(Error::NoPendingChallenge)?;
+
+    let summoner = summoners::get_by_puuid(&challenge.account.puuid).await?;
+    if summoner.profile_icon_id != challenge.icon_id {
+        return Err(Error::ChallengeFailed);
+    }
+
+    state.link_challenges.remove(&session.name);
+
+    let session = UserSession {
+        account: Some(challenge.account),
+        ..session
+    };
+
+    session.set_cookies(cookies)?;
+    LoginResponse::new(&session).map(Json)
+}
@@ -0,0 +1,135 @@
+use rocket::{delete, get, post, serde::json::Json};
+use serde::Deserialize;
+use tracing::instrument;
+use uuid::Uuid;
+
+use crate::{
+    game::{clock::ClockMode, messages::Message, AdminGameStatus, LobbyOptions, StateKind},
+    session_management::Admin,
+    AppState,
+};
+
+use super::error::Error;
+
+#[derive(Debug, Deserialize)]
+pub struct ForceStateForm {
+    state: StateKind,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateGameForm {
+    /// Seed reproducing the roles and timings of a previous game, found in its status
+    seed: Option<u64>,
+    #[serde(flatten)]
+    options: LobbyOptions,
+}
+
+/// Lists all the games, with the roles of their players
+#[get("/admin/games")]
+#[instrument(skip_all)]
+pub async fn list_games(_admin: Admin, state: &AppState) -> Json<Vec<AdminGameStatus>> {
+    let handles = state
+        .games
+        .iter()
+        .map(|e| e.value().clone())
+        .collect::<Vec<_>>();
+    let mut games = Vec::with_capacity(handles.len());
+
+    for game in handles {
+        games.push(game.read().await.get_admin_status());
+    }
+
+    Json(games)
+}
+
+/// Creates an empty game, whose host will be the first player to join it
+#[post("/admin/games", data = "<form>")]
+#[instrument(skip_all)]
+pub async fn create_game(
+    _admin: Admin,
+    state: &AppState,
+    form: Option<Json<CreateGameForm>>,
+) -> Json<Uuid> {
+    let form = form.map(Json::into_inner).unwrap_or_default();
+    Json(state.create_game(form.seed, form.options).0)
+}
+
+/// Moves a game to the given state, regardless of its current one
+#[post("/admin/games/<uid>/state", format = "json", data = "<form>")]
+#[instrument(skip_all, fields(game = %uid, state = form.state.as_str()))]
+pub async fn force_state(
+    _admin: Admin,
+    state: &AppState,
+    uid: Uuid,
+    form: Json<ForceStateForm>,
+) -> Result<(), Error> {
+    state
+        .get_game_by_id(uid)
+        .ok_or(Error::NotFound)?
+        .write()
+        .await
+        .force_state(form.state)
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ClockForm {
+    mode: Option<ClockMode>,
+    /// Game time to jump to, in seconds, requires a manual clock
+    game_time: Option<u64>,
+}
+
+/// Changes the mode of the clock of a game, or sets its game time to trigger the timed events
+#[post("/admin/games/<uid>/clock", format = "json", data = "<form>")]
+#[instrument(skip_all, fields(game = %uid))]
+pub async fn set_clock(
+    _admin: Admin,
+    state: &AppState,
+    uid: Uuid,
+    form: Json<ClockForm>,
+) -> Result<(), Error> {
+    state
+        .get_game_by_id(uid)
+        .ok_or(Error::NotFound)?
+        .write()
+        .await
+        .set_clock(form.mode, form.game_time)
+}
+
+/// Deletes a game, even if players are still in it
+#[delete("/admin/games/<uid>")]
+#[instrument(skip_all, fields(game = %uid))]
+pub async fn delete_game(_admin: Admin, state: &AppState, uid: Uuid) -> Result<(), Error> {
+    state
+        .remove_game(uid)
+        .await
+        .map(|_| ())
+        .ok_or(Error::NotFound)
+}
+
+/// Returns the messages waiting to be fetched by a player, without removing them
+#[get("/admin/players/<name>/messages")]
+#[instrument(skip_all, fields(player = %name))]
+pub async fn peek_messages(
+    _admin: Admin,
+    state: &AppState,
+    name: &str,
+) -> Result<Json<Vec<Message>>, Error> {
+    let proxy = state
+        .messages
+        .get(name)
+        .map(|p| p.clone())
+        .ok_or(Error::NotFound)?;
+
+    let messages = proxy.messages.lock().unwrap().clone();
+    Ok(Json(messages))
+}
+
+/// Removes the proxies of the players who are not in a game and have not been seen for a while, without waiting
+/// for the janitor
+///
+/// Returns the names of the removed proxies
+#[delete("/admin/proxies")]
+#[instrument(skip_all)]
+pub async fn remove_stale_proxies(_admin: Admin, state: &AppState) -> Json<Vec<String>> {
+    Json(state.remove_stale_proxies())
+}
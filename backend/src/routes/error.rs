@@ -16,10 +16,21 @@ pub enum Error {
     MaxPlayerReached,
     NotEnoughPlayers,
     InvalidName,
+    InvalidSecret,
+    NameTaken,
     VotesNotReady,
     VotesClosed,
+    InvalidBallot,
+    InvalidForm,
     AlreadyStarted,
     IncorrectState,
+    InvalidRiotId,
+    NoPendingChallenge,
+    ChallengeFailed,
+    SummonerAlreadyBound,
+    WrongTeam,
+    InvalidToken,
+    LolApiDisabled,
     Internal { msg: String },
 }
 
@@ -29,10 +40,15 @@ impl<'r, 'o: 'r> Responder<'r, 'o> for Error {
             Error::NotFound => Status::NotFound,
             Error::Internal { .. } => Status::InternalServerError,
             Error::Unauthorized => Status::Forbidden,
+            Error::InvalidToken => Status::Unauthorized,
+            Error::NameTaken => Status::Forbidden,
+            Error::LolApiDisabled => Status::ServiceUnavailable,
             _ => Status::BadRequest,
         };
+        crate::metrics::record_error(&self);
+
         let Ok(body) = serde_json::to_string(&self) else {
-            return Err(Status::InternalServerError)
+            return Err(Status::InternalServerError);
         };
 
         Response::build()
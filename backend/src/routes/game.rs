@@ -1,27 +1,42 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::Arc};
 
-use rocket::{get, post, serde::json::Json};
+use rocket::{
+    delete, get, post,
+    serde::json::{self, Json},
+};
+use serde::Deserialize;
+use tokio::sync::RwLock;
+use tracing::{field, instrument, Span};
 use uuid::Uuid;
 
 use crate::{
-    game::{team_builder::Role, AuthenticatedGameStatus, GameStatus},
+    game::{
+        player::bot::VoteStrategy, team_builder::Role, AuthenticatedGameStatus, GameState,
+        GameStatus, LobbyOptions,
+    },
+    models::{AllGameData, Team},
     session_management::UserSession,
+    state::State,
     AppState,
 };
 
 use super::error::Error;
 
+/// Returns the game of a player, recording its uid in the span of the route
+fn player_game(state: &State, name: &str) -> Option<(Uuid, Arc<RwLock<GameState>>)> {
+    let game = state.get_game_by_player(name)?;
+    Span::current().record("game", field::display(game.0));
+    Some(game)
+}
+
 #[get("/game", rank = 0)]
+#[instrument(skip_all, fields(player = %player.name, game = field::Empty))]
 pub async fn get_current_game_authenticated(
     player: UserSession,
     state: &AppState,
 ) -> Result<Json<AuthenticatedGameStatus>, Error> {
     Ok(Json(
-        state
-            .lock()
-            .await
-            .get_game_by_player(&player.name)
-            .await
+        player_game(state, &player.name)
             .ok_or(Error::NotFound)?
             .1
             .read()
@@ -32,6 +47,7 @@ pub async fn get_current_game_authenticated(
 }
 
 #[get("/game/<uid>", rank = 1)]
+#[instrument(skip_all, fields(player = %player.name, game = %uid))]
 pub async fn get_game_authenticated(
     player: UserSession,
     state: &AppState,
@@ -39,10 +55,7 @@ pub async fn get_game_authenticated(
 ) -> Result<Json<AuthenticatedGameStatus>, Error> {
     Ok(Json(
         state
-            .lock()
-            .await
-            .games
-            .get(&uid)
+            .get_game_by_id(uid)
             .ok_or(Error::NotFound)?
             .read()
             .await
@@ -52,13 +65,11 @@ pub async fn get_game_authenticated(
 }
 
 #[get("/game/<uid>", rank = 2)]
+#[instrument(skip_all, fields(game = %uid))]
 pub async fn get_game(state: &AppState, uid: Uuid) -> Result<Json<GameStatus>, Error> {
     Ok(Json(
         state
-            .lock()
-            .await
-            .games
-            .get(&uid)
+            .get_game_by_id(uid)
             .ok_or(Error::NotFound)?
             .read()
             .await
@@ -67,18 +78,27 @@ pub async fn get_game(state: &AppState, uid: Uuid) -> Result<Json<GameStatus>, E
     ))
 }
 
-#[post("/game")]
-pub async fn create_game(player: UserSession, state: &AppState) -> Result<Json<Uuid>, Error> {
-    let mut lock = state.lock().await;
+#[derive(Debug, Default, Deserialize)]
+pub struct CreateGameForm {
+    #[serde(flatten)]
+    options: LobbyOptions,
+}
 
-    if lock.get_game_by_player(&player.name).await.is_some() {
+#[post("/game", data = "<form>")]
+#[instrument(skip_all, fields(player = %player.name, game = field::Empty))]
+pub async fn create_game(
+    player: UserSession,
+    state: &AppState,
+    form: Option<Json<CreateGameForm>>,
+) -> Result<Json<Uuid>, Error> {
+    if state.get_game_by_player(&player.name).is_some() {
         return Err(Error::AlreadyInGame);
     }
 
-    let proxy = lock.get_or_create_proxy(&player.name);
-    let (uid, game) = lock.create_game();
-
-    drop(lock);
+    let form = form.map(Json::into_inner).unwrap_or_default();
+    let proxy = state.get_or_create_proxy(&player.name);
+    let (uid, game) = state.create_game(None, form.options);
+    Span::current().record("game", field::display(uid));
 
     game.write().await.add_player(player, proxy).await?;
 
@@ -86,11 +106,10 @@ pub async fn create_game(player: UserSession, state: &AppState) -> Result<Json<U
 }
 
 #[post("/game/<uid>/join")]
+#[instrument(skip_all, fields(player = %player.name, game = %uid))]
 pub async fn join_game(player: UserSession, state: &AppState, uid: Uuid) -> Result<(), Error> {
-    let lock = state.lock().await;
-    let proxy = lock.get_or_create_proxy(&player.name);
-    let game = lock.get_game_by_id(uid).ok_or(Error::NotFound)?;
-    drop(lock);
+    let proxy = state.get_or_create_proxy(&player.name);
+    let game = state.get_game_by_id(uid).ok_or(Error::NotFound)?;
 
     game.write().await.add_player(player, proxy).await?;
 
@@ -98,28 +117,20 @@ pub async fn join_game(player: UserSession, state: &AppState, uid: Uuid) -> Resu
 }
 
 #[post("/game/quit")]
+#[instrument(skip_all, fields(player = %player.name, game = field::Empty))]
 pub async fn quit_game(player: UserSession, state: &AppState) -> Result<(), Error> {
-    let game = state
-        .lock()
-        .await
-        .get_game_by_player(&player.name)
-        .await
-        .ok_or(Error::NotInGame)?;
+    let game = player_game(state, &player.name).ok_or(Error::NotInGame)?;
 
     game.1.write().await.remove_player(player.name).await?;
-    state.lock().await.try_remove_game(game.0).await;
+    state.try_remove_game(game.0).await;
 
     Ok(())
 }
 
 #[post("/game/start")]
+#[instrument(skip_all, fields(player = %player.name, game = field::Empty))]
 pub async fn start_game(player: UserSession, state: &AppState) -> Result<(), Error> {
-    let game = state
-        .lock()
-        .await
-        .get_game_by_player(&player.name)
-        .await
-        .ok_or(Error::NotInGame)?;
+    let game = player_game(state, &player.name).ok_or(Error::NotInGame)?;
 
     game.1.write().await.start().await?;
 
@@ -127,13 +138,9 @@ pub async fn start_game(player: UserSession, state: &AppState) -> Result<(), Err
 }
 
 #[post("/game/end")]
+#[instrument(skip_all, fields(player = %player.name, game = field::Empty))]
 pub async fn end_game(player: UserSession, state: &AppState) -> Result<(), Error> {
-    let game = state
-        .lock()
-        .await
-        .get_game_by_player(&player.name)
-        .await
-        .ok_or(Error::NotInGame)?;
+    let game = player_game(state, &player.name).ok_or(Error::NotInGame)?;
 
     game.1.write().await.end().await?;
 
@@ -141,17 +148,13 @@ pub async fn end_game(player: UserSession, state: &AppState) -> Result<(), Error
 }
 
 #[post("/game/votes", format = "json", data = "<votes>")]
+#[instrument(skip_all, fields(player = %player.name, game = field::Empty))]
 pub async fn post_votes(
     player: UserSession,
     state: &AppState,
     votes: Json<HashMap<String, Role>>,
 ) -> Result<(), Error> {
-    let game = state
-        .lock()
-        .await
-        .get_game_by_player(&player.name)
-        .await
-        .ok_or(Error::NotInGame)?;
+    let game = player_game(state, &player.name).ok_or(Error::NotInGame)?;
 
     game.1
         .write()
@@ -161,3 +164,114 @@ pub async fn post_votes(
 
     Ok(())
 }
+
+#[post("/game/live", format = "json", data = "<data>")]
+#[instrument(skip_all, fields(player = %player.name, game = field::Empty))]
+pub async fn post_live_data(
+    player: UserSession,
+    state: &AppState,
+    data: Json<AllGameData>,
+) -> Result<(), Error> {
+    let game = player_game(state, &player.name).ok_or(Error::NotInGame)?;
+
+    game.1
+        .write()
+        .await
+        .receive_live_data(&player.name, data.into_inner().into())
+        .await?;
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct BindForm {
+    /// Name of the player to bind
+    player: String,
+    /// Summoner played by this player
+    summoner_name: String,
+}
+
+#[post("/game/bind", format = "json", data = "<form>")]
+#[instrument(skip_all, fields(player = %player.name, game = field::Empty))]
+pub async fn bind_summoner(
+    player: UserSession,
+    state: &AppState,
+    form: Json<BindForm>,
+) -> Result<(), Error> {
+    let game = player_game(state, &player.name).ok_or(Error::NotInGame)?;
+
+    let form = form.into_inner();
+    game.1
+        .write()
+        .await
+        .bind_summoner(&player.name, &form.player, form.summoner_name)?;
+
+    Ok(())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TeamForm {
+    /// Name of the player to move, the host can move anyone
+    player: Option<String>,
+    team: Team,
+}
+
+/// Changes the side of a player in a versus lobby
+#[post("/game/team", format = "json", data = "<form>")]
+#[instrument(skip_all, fields(player = %player.name, game = field::Empty))]
+pub async fn set_team(
+    player: UserSession,
+    state: &AppState,
+    form: Json<TeamForm>,
+) -> Result<(), Error> {
+    let game = player_game(state, &player.name).ok_or(Error::NotInGame)?;
+
+    let form = form.into_inner();
+    let target = form.player.as_deref().unwrap_or(&player.name);
+    game.1
+        .write()
+        .await
+        .set_team(&player.name, target, form.team)?;
+
+    Ok(())
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BotForm {
+    #[serde(default)]
+    strategy: VoteStrategy,
+}
+
+/// Adds a bot to the lobby of the host
+///
+/// Returns the name of the bot
+#[post("/game/bots", data = "<form>")]
+#[instrument(skip_all, fields(player = %player.name, game = field::Empty))]
+pub async fn add_bot(
+    player: UserSession,
+    state: &AppState,
+    form: Result<Json<BotForm>, json::Error<'_>>,
+) -> Result<Json<String>, Error> {
+    let game = player_game(state, &player.name).ok_or(Error::NotInGame)?;
+
+    // The form is optional, but a body which does not parse must not fall back to the default strategy
+    let form = match form {
+        Ok(form) => form.into_inner(),
+        Err(json::Error::Parse(body, _)) if body.trim().is_empty() => BotForm::default(),
+        Err(_) => return Err(Error::InvalidForm),
+    };
+    let name = game.1.write().await.add_bot(&player.name, form.strategy)?;
+
+    Ok(Json(name))
+}
+
+/// Removes a bot from the lobby of the host
+#[delete("/game/bots/<name>")]
+#[instrument(skip_all, fields(player = %player.name, game = field::Empty, bot = %name))]
+pub async fn remove_bot(player: UserSession, state: &AppState, name: &str) -> Result<(), Error> {
+    let game = player_game(state, &player.name).ok_or(Error::NotInGame)?;
+
+    game.1.write().await.remove_bot(&player.name, name).await?;
+
+    Ok(())
+}
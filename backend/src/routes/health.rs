@@ -0,0 +1,117 @@
+//! Liveness and readiness probes, mounted outside of the API base uri
+//!
+//! `/health` only fails when the process cannot make progress, and is meant to trigger restarts. `/ready` also checks
+//! the external dependencies, and fails while the server cannot fully serve the players.
+
+use std::time::Duration;
+
+use rocket::{get, http::Status, serde::json::Json};
+use serde::Serialize;
+use tracing::instrument;
+
+use crate::{lol_api::status, routes::error::Error, AppState};
+
+/// Lag above which the scheduler is considered stuck
+const MAX_SCHEDULER_LAG: Duration = Duration::from_secs(30);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CheckStatus {
+    Ok,
+    Failing,
+    /// The checked feature is not configured
+    Disabled,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SchedulerHealth {
+    status: CheckStatus,
+    /// Time elapsed since the earliest overdue deadline, in milliseconds
+    lag_ms: u128,
+    /// Number of games waiting for a deadline
+    pending: usize,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HealthReport {
+    status: CheckStatus,
+    /// Time since the server started, in seconds
+    uptime: u64,
+    scheduler: SchedulerHealth,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ReadinessReport {
+    status: CheckStatus,
+    scheduler: SchedulerHealth,
+    /// Games are only kept in memory, so there is no storage to check yet
+    persistence: CheckStatus,
+    riot_api: CheckStatus,
+}
+
+impl SchedulerHealth {
+    fn check(state: &AppState) -> Self {
+        let lag = state.scheduler.lag();
+
+        Self {
+            status: if lag < MAX_SCHEDULER_LAG {
+                CheckStatus::Ok
+            } else {
+                CheckStatus::Failing
+            },
+            lag_ms: lag.as_millis(),
+            pending: state.scheduler.pending(),
+        }
+    }
+}
+
+/// Returns [Status::Ok] if none of the checks fail, [Status::ServiceUnavailable] otherwise
+fn http_status(checks: &[CheckStatus]) -> (Status, CheckStatus) {
+    if checks.contains(&CheckStatus::Failing) {
+        (Status::ServiceUnavailable, CheckStatus::Failing)
+    } else {
+        (Status::Ok, CheckStatus::Ok)
+    }
+}
+
+/// Checks that the server is alive and that the games are processed in time
+#[get("/health")]
+#[instrument(skip_all)]
+pub async fn health(state: &AppState) -> (Status, Json<HealthReport>) {
+    let scheduler = SchedulerHealth::check(state);
+    let (code, status) = http_status(&[scheduler.status]);
+
+    (
+        code,
+        Json(HealthReport {
+            status,
+            uptime: state.started.elapsed().as_secs(),
+            scheduler,
+        }),
+    )
+}
+
+/// Checks that the server and its dependencies are able to serve the players
+#[get("/ready")]
+#[instrument(skip_all)]
+pub async fn ready(state: &AppState) -> (Status, Json<ReadinessReport>) {
+    let scheduler = SchedulerHealth::check(state);
+    let persistence = CheckStatus::Disabled;
+    let riot_api = match status::is_reachable().await {
+        Ok(true) => CheckStatus::Ok,
+        Ok(false) => CheckStatus::Failing,
+        Err(Error::LolApiDisabled) => CheckStatus::Disabled,
+        Err(_) => CheckStatus::Failing,
+    };
+    let (code, status) = http_status(&[scheduler.status, persistence, riot_api]);
+
+    (
+        code,
+        Json(ReadinessReport {
+            status,
+            scheduler,
+            persistence,
+            riot_api,
+        }),
+    )
+}
@@ -1,52 +1,139 @@
 use self::error::Error;
 use crate::game::messages::Message;
-use crate::session_management::UserSession;
+use crate::session_management::{RefreshCookie, UserSession};
 use crate::AppState;
+use rocket::http::CookieJar;
 use rocket::serde::json::Json;
-use rocket::{get, post};
+use rocket::{catch, get, post};
 use serde::{Deserialize, Serialize};
+use std::sync::OnceLock;
+use tokio::sync::Semaphore;
+use tracing::{field, instrument, Span};
 
+pub mod account;
+pub mod admin;
 pub mod error;
 pub mod game;
+pub mod health;
 
 #[derive(Debug, Deserialize)]
 pub struct LoginForm {
     name: String,
+    /// Secret proving the ownership of the name, set on the first login
+    secret: String,
 }
 
 #[derive(Debug, Serialize)]
 pub struct LoginResponse {
     token: String,
+    refresh_token: String,
+}
+
+impl LoginResponse {
+    /// Issues a new pair of tokens for a session
+    pub fn new(session: &UserSession) -> Result<Self, Error> {
+        Ok(Self {
+            token: session.encode().map_err(Error::from)?,
+            refresh_token: session.encode_refresh().map_err(Error::from)?,
+        })
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RefreshForm {
+    refresh_token: String,
+}
+
+/// Limits the number of secrets hashed at once, each hash using a lot of memory
+fn hashing_permits() -> &'static Semaphore {
+    static PERMITS: OnceLock<Semaphore> = OnceLock::new();
+    PERMITS
+        .get_or_init(|| Semaphore::new(std::thread::available_parallelism().map_or(1, |n| n.get())))
 }
 
 #[post("/login", format = "json", data = "<login_form>")]
+#[instrument(skip_all, fields(player = %login_form.name))]
 pub async fn login(
     state: &AppState,
+    cookies: &CookieJar<'_>,
     login_form: Json<LoginForm>,
 ) -> Result<Json<LoginResponse>, Error> {
-    let state = state.lock().await;
-    let session = UserSession::new(login_form.name.clone()).await?;
+    let mut session = UserSession::new(login_form.name.clone()).await?;
+
+    // Hashing the secret is slow, and would block the other requests if done on the async workers
+    let _permit = hashing_permits().acquire().await.map_err(Error::from)?;
+    let (claimer, name, secret) = (
+        state.inner().clone(),
+        session.name.clone(),
+        login_form.into_inner().secret,
+    );
+    session.reservation = tokio::task::spawn_blocking(move || claimer.claim_name(&name, &secret))
+        .await
+        .map_err(Error::from)??;
     state.get_or_create_proxy(&session.name);
 
-    session
-        .encode()
-        .map(|t| Json(LoginResponse { token: t }))
-        .map_err(Error::from)
+    session.set_cookies(cookies)?;
+    LoginResponse::new(&session).map(Json)
+}
+
+/// Exchanges a refresh token, from the body or the cookies, for a new pair of tokens
+#[post("/refresh", data = "<form>")]
+#[instrument(skip_all, fields(player = field::Empty))]
+pub async fn refresh(
+    state: &AppState,
+    cookies: &CookieJar<'_>,
+    refresh_cookie: RefreshCookie,
+    form: Option<Json<RefreshForm>>,
+) -> Result<Json<LoginResponse>, Error> {
+    let token = match form {
+        Some(form) => form.into_inner().refresh_token,
+        None => refresh_cookie.token()?,
+    };
+    let token = UserSession::decode_refresh(&token).map_err(|_| Error::InvalidToken)?;
+    let session = token.session;
+    Span::current().record("player", field::display(&session.name));
+
+    state
+        .check_session(&session)
+        .map_err(|_| Error::InvalidToken)?;
+    // The token is rotated, so that a leaked one stops working once its owner refreshed
+    if !state.spend_refresh_token(token.id, token.exp) {
+        return Err(Error::InvalidToken);
+    }
+
+    session.set_cookies(cookies)?;
+    LoginResponse::new(&session).map(Json)
+}
+
+/// Revokes all the tokens of the current session
+#[post("/logout")]
+#[instrument(skip_all, fields(player = %session.name))]
+pub async fn logout(
+    session: UserSession,
+    state: &AppState,
+    cookies: &CookieJar<'_>,
+) -> Result<(), Error> {
+    state.revoke_session(session.sid);
+    UserSession::remove_cookies(cookies);
+    Ok(())
+}
+
+/// Answers the requests rejected by the session guard, so that clients know to refresh their access token
+#[catch(401)]
+pub fn unauthorized() -> Error {
+    Error::InvalidToken
 }
 
 #[get("/updates")]
+#[instrument(skip_all, fields(player = %session.name))]
 pub async fn get_updates(
     session: UserSession,
     state: &AppState,
 ) -> Result<Json<Vec<Message>>, Error> {
-    let state = state.lock().await;
-
-    let lock = state.messages.lock().unwrap();
-    let Some(messages_mutex) = lock.get(&session.name) else {
-        return Err(Error::NotFound)
-    };
+    // The proxy may have been collected while the player was away
+    let proxy = state.get_or_create_proxy(&session.name);
 
-    let mut vec = messages_mutex.messages.lock().unwrap();
+    let mut vec = proxy.messages.lock().unwrap();
     let messages = vec.clone();
     *vec = vec![];
 
@@ -0,0 +1,158 @@
+//! Single background task processing the timed events of all the games
+//!
+//! Each game schedules its next deadline (missions, flips, vote timeouts, API polls...), and is woken up exactly when
+//! it is due, instead of every game polling its own state at a fixed interval.
+
+use std::{
+    cmp::Reverse,
+    collections::{BinaryHeap, HashMap},
+    sync::{Arc, Mutex, Weak},
+    time::{Duration, Instant},
+};
+
+use tokio::sync::Notify;
+use tracing::{info_span, Instrument};
+use uuid::Uuid;
+
+use crate::{game::GameState, state::State};
+
+/// Longest sleep of the scheduler, so that it notices when the state is dropped
+const MAX_SLEEP: Duration = Duration::from_secs(60);
+
+#[derive(Default)]
+struct Queue {
+    heap: BinaryHeap<Reverse<(Instant, Uuid)>>,
+    /// Current deadline of each game, entries of the heap not matching it are outdated
+    deadlines: HashMap<Uuid, Instant>,
+}
+
+#[derive(Default)]
+struct Inner {
+    queue: Mutex<Queue>,
+    /// Wakes up the scheduler when a deadline earlier than the ones it is waiting for is added
+    notify: Notify,
+}
+
+/// Handle to the scheduler, shared by all the games
+#[derive(Clone, Default)]
+pub struct Scheduler {
+    inner: Arc<Inner>,
+}
+
+impl std::fmt::Debug for Scheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("pending", &self.pending())
+            .finish()
+    }
+}
+
+impl Scheduler {
+    /// Sets the next deadline of a game, replacing the previous one
+    ///
+    /// - uid: Uuid of the game
+    /// - at: Instant at which the game must be ticked, or `None` if it has nothing to do
+    pub fn schedule(&self, uid: Uuid, at: Option<Instant>) {
+        let mut queue = self.inner.queue.lock().unwrap();
+
+        match at {
+            Some(at) => {
+                if queue.deadlines.insert(uid, at) == Some(at) {
+                    return;
+                }
+
+                let earliest = queue.heap.peek().map(|Reverse((t, _))| *t);
+                queue.heap.push(Reverse((at, uid)));
+
+                if !matches!(earliest, Some(t) if t <= at) {
+                    self.inner.notify.notify_one();
+                }
+            }
+            None => {
+                queue.deadlines.remove(&uid);
+            }
+        }
+    }
+
+    /// Removes the deadline of a game
+    pub fn cancel(&self, uid: Uuid) {
+        self.schedule(uid, None)
+    }
+
+    /// Number of games waiting for a deadline
+    pub fn pending(&self) -> usize {
+        self.inner.queue.lock().unwrap().deadlines.len()
+    }
+
+    /// Time elapsed since the earliest deadline which is due but was not processed yet
+    ///
+    /// Stays close to zero while the scheduler keeps up, and grows if it is stuck or overloaded.
+    pub fn lag(&self) -> Duration {
+        let mut queue = self.inner.queue.lock().unwrap();
+
+        while let Some(Reverse((at, uid))) = queue.heap.peek().cloned() {
+            if queue.deadlines.get(&uid) == Some(&at) {
+                return Instant::now().saturating_duration_since(at);
+            }
+            queue.heap.pop();
+        }
+
+        Duration::ZERO
+    }
+
+    /// Pops the games whose deadline is due, and returns the instant of the next deadline
+    fn pop_due(&self, now: Instant) -> (Vec<Uuid>, Option<Instant>) {
+        let mut queue = self.inner.queue.lock().unwrap();
+        let mut due = Vec::new();
+
+        while let Some(Reverse((at, uid))) = queue.heap.peek().cloned() {
+            if queue.deadlines.get(&uid) != Some(&at) {
+                queue.heap.pop();
+                continue;
+            }
+            if at > now {
+                return (due, Some(at));
+            }
+
+            queue.heap.pop();
+            queue.deadlines.remove(&uid);
+            due.push(uid);
+        }
+
+        (due, None)
+    }
+
+    /// Background task ticking the games when their deadline is due, until the state is dropped
+    pub async fn run(self, state: Weak<State>) {
+        loop {
+            let (due, next) = self.pop_due(Instant::now());
+
+            if !due.is_empty() {
+                let Some(state) = state.upgrade() else {
+                    break;
+                };
+
+                // Games are ticked concurrently, so that a slow call to the Riot API does not delay the others
+                for uid in due {
+                    if let Some(game) = state.get_game_by_id(uid) {
+                        tokio::spawn(
+                            GameState::tick(game).instrument(info_span!("tick", game = %uid)),
+                        );
+                    }
+                }
+            } else if state.strong_count() == 0 {
+                break;
+            }
+
+            let sleep = next
+                .map(|at| at.saturating_duration_since(Instant::now()))
+                .unwrap_or(MAX_SLEEP)
+                .min(MAX_SLEEP);
+
+            tokio::select! {
+                _ = tokio::time::sleep(sleep) => {}
+                _ = self.inner.notify.notified() => {}
+            }
+        }
+    }
+}
@@ -1,19 +1,145 @@
-use jsonwebtoken::{DecodingKey, EncodingKey, Header, Validation};
+use argon2::{
+    password_hash::{rand_core::OsRng, PasswordHash, SaltString},
+    Argon2, PasswordHasher, PasswordVerifier,
+};
+use jsonwebtoken::get_current_timestamp;
 use rocket::{
-    http::{Cookie, Status},
+    http::{Cookie, CookieJar, Method, SameSite, Status},
     request::{FromRequest, Outcome},
+    time::Duration as CookieDuration,
     Request,
 };
 use serde::{Deserialize, Serialize};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
 
-use crate::{env::env_config, routes::error::Error};
+use crate::{env::env_config, game::player::bot, keyring::keyring, routes::error::Error, AppState};
 
-const SESSION_COOKIE_NAME: &str = "session";
+/// Distinct from the `session` cookie set by the frontend itself
+const SESSION_COOKIE_NAME: &str = "access";
+const REFRESH_COOKIE_NAME: &str = "refresh";
+/// Cookie readable by the frontend, whose value must be echoed in [CSRF_HEADER_NAME]
+const CSRF_COOKIE_NAME: &str = "csrf";
+const CSRF_HEADER_NAME: &str = "X-CSRF-Token";
+const ADMIN_HEADER_NAME: &str = "X-Admin-Token";
 
 /// Guard for the user's session
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct UserSession {
     pub name: String,
+    /// League account linked through the icon challenge, if any
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub account: Option<LinkedAccount>,
+    /// Id of the login session, shared by all the tokens issued from the same login
+    pub sid: Uuid,
+    /// Id of the reservation of the name, invalidating the session once the name is claimed by someone else
+    pub reservation: Uuid,
+    /// Timestamp of the login, kept by the refreshed tokens so that they cannot extend the session
+    pub auth_time: u64,
+}
+
+/// Kind of token issued for a session
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenType {
+    /// Short-lived token authenticating requests
+    Access,
+    /// Long-lived token used to obtain new access tokens
+    Refresh,
+}
+
+impl TokenType {
+    /// Lifetime of the token, in seconds
+    fn duration(self) -> u64 {
+        match self {
+            TokenType::Access => env_config().access_token_duration,
+            TokenType::Refresh => env_config().refresh_token_duration,
+        }
+    }
+}
+
+/// Claims of an encoded token
+#[derive(Debug, Serialize, Deserialize)]
+struct Claims {
+    #[serde(flatten)]
+    session: UserSession,
+    typ: TokenType,
+    /// Id of the token, so that a refresh token can only be exchanged once
+    jti: Uuid,
+    iat: u64,
+    exp: u64,
+}
+
+/// Refresh token presented in exchange for a new pair of tokens
+#[derive(Debug)]
+pub struct RefreshToken {
+    pub session: UserSession,
+    pub id: Uuid,
+    /// Expiration timestamp of the token
+    pub exp: u64,
+}
+
+/// League account bound to a session
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct LinkedAccount {
+    pub puuid: String,
+    pub summoner_id: String,
+    pub game_name: String,
+    pub tag_line: String,
+}
+
+impl LinkedAccount {
+    /// Whether the given summoner name, as displayed by the LoL client, designates this account
+    pub fn is_summoner_name(&self, name: &str) -> bool {
+        name == self.game_name || name == format!("{}#{}", self.game_name, self.tag_line)
+    }
+}
+
+/// Pending proof of ownership of a League account
+///
+/// The account is linked once its profile icon has been set to `icon_id`, before `expires`
+#[derive(Debug, Clone)]
+pub struct LinkChallenge {
+    pub account: LinkedAccount,
+    pub icon_id: i32,
+    pub expires: Instant,
+}
+
+/// Ownership of a player name, proven by a secret chosen on the first login
+#[derive(Debug, Clone)]
+pub struct NameReservation {
+    pub id: Uuid,
+    secret_hash: String,
+    pub last_seen: Instant,
+}
+
+impl NameReservation {
+    /// Reserves a name for the given secret
+    pub fn new(secret: &str) -> Result<Self, Error> {
+        let salt = SaltString::generate(&mut OsRng);
+
+        Ok(Self {
+            id: Uuid::new_v4(),
+            secret_hash: Argon2::default()
+                .hash_password(secret.as_bytes(), &salt)
+                .map_err(Error::from)?
+                .to_string(),
+            last_seen: Instant::now(),
+        })
+    }
+
+    /// Returns whether the secret is the one used to reserve the name
+    pub fn verify(&self, secret: &str) -> Result<bool, Error> {
+        let hash = PasswordHash::new(&self.secret_hash).map_err(Error::from)?;
+        Ok(Argon2::default()
+            .verify_password(secret.as_bytes(), &hash)
+            .is_ok())
+    }
+
+    /// Returns whether the name has been inactive long enough to be claimed by someone else
+    pub fn is_expired(&self) -> bool {
+        self.last_seen.elapsed() > Duration::from_secs(env_config().name_reservation_timeout)
+    }
 }
 
 #[derive(Debug)]
@@ -21,6 +147,50 @@ pub enum UserSessionError {
     Missing,
     BadFormat,
     Invalid(jsonwebtoken::errors::Error),
+    WrongType,
+    Revoked,
+    NameReleased,
+    Csrf,
+}
+
+/// Guard for the operators of the server, authenticated by the ADMIN_TOKEN
+#[derive(Debug)]
+pub struct Admin;
+
+#[derive(Debug)]
+pub enum AdminError {
+    /// No ADMIN_TOKEN is configured
+    Disabled,
+    Missing,
+    Invalid,
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for Admin {
+    type Error = AdminError;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        let Some(ref expected) = env_config().admin_token else {
+            return Outcome::Error((Status::NotFound, AdminError::Disabled));
+        };
+        let Some(token) = req.headers().get_one(ADMIN_HEADER_NAME) else {
+            return Outcome::Error((Status::Unauthorized, AdminError::Missing));
+        };
+
+        // Compares the whole token, so that the time taken does not reveal the length of the matching prefix
+        let matches = token.len() == expected.len()
+            && token
+                .bytes()
+                .zip(expected.bytes())
+                .fold(0, |acc, (a, b)| acc | (a ^ b))
+                == 0;
+
+        if matches {
+            Outcome::Success(Admin)
+        } else {
+            Outcome::Error((Status::Forbidden, AdminError::Invalid))
+        }
+    }
 }
 
 #[rocket::async_trait]
@@ -28,42 +198,148 @@ impl<'r> FromRequest<'r> for UserSession {
     type Error = UserSessionError;
 
     async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
-        if let Some(header) = req.headers().get_one("Authorization") {
+        let token = if let Some(header) = req.headers().get_one("Authorization") {
             let Some(header) = header.strip_prefix("Bearer ") else {
-                return Outcome::Failure((Status::BadRequest, UserSessionError::BadFormat))
+                return Outcome::Error((Status::BadRequest, UserSessionError::BadFormat));
             };
-
-            let mut validation = Validation::default();
-            validation.validate_exp = false;
-            validation.required_spec_claims.clear();
-
-            match jsonwebtoken::decode::<UserSession>(
-                header,
-                &DecodingKey::from_secret(env_config().jwt_secret.as_ref()),
-                &validation,
-            ) {
-                Ok(session) => Outcome::Success(session.claims),
-                Err(e) => Outcome::Failure((Status::BadRequest, UserSessionError::Invalid(e))),
+            header.to_owned()
+        } else if let Some(cookie) = req.cookies().get(SESSION_COOKIE_NAME) {
+            // Browsers attach cookies to cross-site requests, so state-changing ones must prove
+            // that they come from the frontend
+            if !matches!(req.method(), Method::Get | Method::Head | Method::Options)
+                && !has_csrf_proof(req)
+            {
+                return Outcome::Error((Status::Forbidden, UserSessionError::Csrf));
             }
+            cookie.value().to_owned()
         } else {
-            Outcome::Forward(())
+            return Outcome::Forward(Status::Unauthorized);
+        };
+
+        let session = match UserSession::decode(&token, TokenType::Access) {
+            Ok(session) => session,
+            Err(e) => return Outcome::Error((Status::Unauthorized, e)),
+        };
+
+        let Outcome::Success(state) = req.guard::<&AppState>().await else {
+            return Outcome::Error((Status::InternalServerError, UserSessionError::Missing));
+        };
+
+        match state.check_session(&session) {
+            Ok(()) => Outcome::Success(session),
+            Err(e) => Outcome::Error((Status::Unauthorized, e)),
         }
     }
 }
 
+/// Whether the request echoes the CSRF cookie in [CSRF_HEADER_NAME], which only the frontend can read
+fn has_csrf_proof(req: &Request<'_>) -> bool {
+    let csrf_cookie = req.cookies().get(CSRF_COOKIE_NAME).map(|c| c.value());
+    let csrf_header = req.headers().get_one(CSRF_HEADER_NAME);
+
+    csrf_cookie.is_some() && csrf_cookie == csrf_header
+}
+
+/// Guard for the refresh token stored in the cookies, for clients not using bearer tokens
+#[derive(Debug)]
+pub struct RefreshCookie {
+    token: Option<String>,
+    csrf_proof: bool,
+}
+
+impl RefreshCookie {
+    /// Returns the refresh token, as long as the request proves that it comes from the frontend like the other
+    /// state-changing requests authenticated by cookies
+    pub fn token(self) -> Result<String, Error> {
+        let token = self.token.ok_or(Error::InvalidToken)?;
+        if self.csrf_proof {
+            Ok(token)
+        } else {
+            Err(Error::Unauthorized)
+        }
+    }
+}
+
+#[rocket::async_trait]
+impl<'r> FromRequest<'r> for RefreshCookie {
+    type Error = std::convert::Infallible;
+
+    async fn from_request(req: &'r Request<'_>) -> Outcome<Self, Self::Error> {
+        Outcome::Success(RefreshCookie {
+            token: req
+                .cookies()
+                .get(REFRESH_COOKIE_NAME)
+                .map(|c| c.value().to_owned()),
+            csrf_proof: has_csrf_proof(req),
+        })
+    }
+}
+
 impl UserSession {
-    /// Creates an encoded string for this session, using the secret stored in JWT_SECRET
+    /// Creates an access token for this session, signed with the current signing key
     pub fn encode(&self) -> Result<String, jsonwebtoken::errors::Error> {
-        jsonwebtoken::encode(
-            &Header::default(),
-            self,
-            &EncodingKey::from_secret(env_config().jwt_secret.as_ref()),
-        )
+        self.encode_token(TokenType::Access)
+    }
+
+    /// Creates a refresh token for this session, signed with the current signing key
+    pub fn encode_refresh(&self) -> Result<String, jsonwebtoken::errors::Error> {
+        self.encode_token(TokenType::Refresh)
     }
 
+    fn encode_token(&self, typ: TokenType) -> Result<String, jsonwebtoken::errors::Error> {
+        let iat = get_current_timestamp();
+        let session_end = self.auth_time + env_config().session_max_duration;
+
+        keyring().encode(&Claims {
+            session: self.clone(),
+            typ,
+            jti: Uuid::new_v4(),
+            iat,
+            exp: (iat + typ.duration()).min(session_end),
+        })
+    }
+
+    /// Decodes a token of the given type, checking its signature and expiration
+    pub fn decode(token: &str, typ: TokenType) -> Result<Self, UserSessionError> {
+        Self::decode_claims(token, typ).map(|claims| claims.session)
+    }
+
+    /// Decodes a refresh token, checking its signature and expiration
+    pub fn decode_refresh(token: &str) -> Result<RefreshToken, UserSessionError> {
+        let claims = Self::decode_claims(token, TokenType::Refresh)?;
+
+        Ok(RefreshToken {
+            session: claims.session,
+            id: claims.jti,
+            exp: claims.exp,
+        })
+    }
+
+    fn decode_claims(token: &str, typ: TokenType) -> Result<Claims, UserSessionError> {
+        let claims = keyring()
+            .decode::<Claims>(token)
+            .map_err(UserSessionError::Invalid)?
+            .claims;
+
+        if claims.typ == typ {
+            Ok(claims)
+        } else {
+            Err(UserSessionError::WrongType)
+        }
+    }
+
+    /// Creates a new session, which is only valid once the reservation of the name is set
+    ///
+    /// The names of the bots are reserved, so that they never collide with a player
     pub async fn new(name: String) -> Result<Self, Error> {
-        if (1..16).contains(&name.len()) {
-            Ok(Self { name })
+        if (1..16).contains(&name.len()) && !name.starts_with(bot::NAME_PREFIX) {
+            Ok(Self {
+                name,
+                account: None,
+                sid: Uuid::new_v4(),
+                reservation: Uuid::nil(),
+                auth_time: get_current_timestamp(),
+            })
         } else {
             Err(Error::InvalidName)
         }
@@ -74,9 +350,53 @@ impl TryFrom<UserSession> for Cookie<'_> {
     type Error = UserSessionError;
 
     fn try_from(value: UserSession) -> Result<Self, Self::Error> {
-        Ok(Cookie::new(
+        Ok(Cookie::build((
             SESSION_COOKIE_NAME,
             value.encode().map_err(UserSessionError::Invalid)?,
         ))
+        .path(env_config().uri.clone())
+        .http_only(true)
+        .same_site(SameSite::Strict)
+        .secure(env_config().secure_cookies)
+        .max_age(CookieDuration::seconds(TokenType::Access.duration() as i64))
+        .build())
+    }
+}
+
+impl UserSession {
+    /// Sets the session, refresh and CSRF cookies, for clients not using bearer tokens
+    pub fn set_cookies(&self, jar: &CookieJar<'_>) -> Result<(), Error> {
+        let config = env_config();
+
+        jar.add(Cookie::try_from(self.clone()).map_err(Error::from)?);
+        jar.add(
+            Cookie::build((
+                REFRESH_COOKIE_NAME,
+                self.encode_refresh().map_err(Error::from)?,
+            ))
+            .path(format!("{}/refresh", config.uri))
+            .http_only(true)
+            .same_site(SameSite::Strict)
+            .secure(config.secure_cookies)
+            .max_age(CookieDuration::seconds(TokenType::Refresh.duration() as i64)),
+        );
+        jar.add(
+            Cookie::build((CSRF_COOKIE_NAME, Uuid::new_v4().simple().to_string()))
+                .path("/")
+                .http_only(false)
+                .same_site(SameSite::Strict)
+                .secure(config.secure_cookies),
+        );
+
+        Ok(())
+    }
+
+    /// Removes the cookies set by [UserSession::set_cookies]
+    pub fn remove_cookies(jar: &CookieJar<'_>) {
+        let config = env_config();
+
+        jar.remove(Cookie::build(SESSION_COOKIE_NAME).path(config.uri.clone()));
+        jar.remove(Cookie::build(REFRESH_COOKIE_NAME).path(format!("{}/refresh", config.uri)));
+        jar.remove(Cookie::build(CSRF_COOKIE_NAME).path("/"));
     }
 }
@@ -1,79 +1,339 @@
-use crate::game::{player::proxy::PlayerProxy, GameState};
+use crate::{
+    env::env_config,
+    game::{player::proxy::PlayerProxy, GameState, LobbyOptions},
+    routes::error::Error,
+    scheduler::Scheduler,
+    session_management::{LinkChallenge, NameReservation, UserSession, UserSessionError},
+};
+use dashmap::{mapref::entry::Entry, DashMap};
+use jsonwebtoken::get_current_timestamp;
 use std::{
-    collections::{hash_map::Entry, HashMap},
-    sync::{Arc, Mutex},
+    sync::{Arc, Weak},
+    time::{Duration, Instant},
 };
 use tokio::sync::RwLock;
 use uuid::Uuid;
 
+/// Interval between two collections of the abandoned games and proxies
+const JANITOR_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Game of each player, shared by the games to keep it up to date as players join and leave
+#[derive(Debug, Clone, Default)]
+pub struct PlayerIndex {
+    games: Arc<DashMap<String, Uuid>>,
+}
+
+impl PlayerIndex {
+    /// Returns the game in which the player is
+    pub fn get(&self, name: &str) -> Option<Uuid> {
+        self.games.get(name).map(|g| *g)
+    }
+
+    /// Records that a player joined a game, failing if they are already in another one
+    pub fn insert(&self, name: &str, uid: Uuid) -> Result<(), Error> {
+        match self.games.entry(name.to_owned()) {
+            Entry::Occupied(e) if *e.get() != uid => Err(Error::AlreadyInGame),
+            Entry::Occupied(_) => Ok(()),
+            Entry::Vacant(e) => {
+                e.insert(uid);
+                Ok(())
+            }
+        }
+    }
+
+    /// Records that a player left a game
+    pub fn remove(&self, name: &str, uid: Uuid) {
+        self.games.remove_if(name, |_, g| *g == uid);
+    }
+
+    /// Number of players in all the games
+    pub fn len(&self) -> usize {
+        self.games.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.games.is_empty()
+    }
+
+    /// Removes all the players of a game
+    pub fn remove_game(&self, uid: Uuid) {
+        self.games.retain(|_, g| *g != uid);
+    }
+}
+
 /// Global state of the app
+///
+/// All the maps are sharded, so that requests on different games or players do not contend. References into them
+/// must never be held across an await point, as it would block the other tasks accessing the same shard.
 pub struct State {
     /// All running games
-    pub games: HashMap<Uuid, Arc<RwLock<GameState>>>,
+    pub games: DashMap<Uuid, Arc<RwLock<GameState>>>,
+    /// Game of each player
+    pub players: PlayerIndex,
     /// Proxies for each logged in player
-    pub messages: Mutex<HashMap<String, PlayerProxy>>,
+    pub messages: DashMap<String, PlayerProxy>,
+    /// Pending account linking challenges, by player name
+    pub link_challenges: DashMap<String, LinkChallenge>,
+    /// Login sessions revoked before the expiration of their tokens, with that expiration timestamp
+    pub revoked_sessions: DashMap<Uuid, u64>,
+    /// Refresh tokens already exchanged, with their expiration timestamp
+    pub spent_refresh_tokens: DashMap<Uuid, u64>,
+    /// Owners of the player names
+    pub names: DashMap<String, NameReservation>,
+    /// Timed events of the games
+    pub scheduler: Scheduler,
+    /// Instant at which the server started
+    pub started: Instant,
 }
 
 impl State {
-    pub fn new() -> Self {
+    pub fn new(scheduler: Scheduler) -> Self {
         Self {
             games: Default::default(),
+            players: Default::default(),
             messages: Default::default(),
+            link_challenges: Default::default(),
+            revoked_sessions: Default::default(),
+            spent_refresh_tokens: Default::default(),
+            names: Default::default(),
+            scheduler,
+            started: Instant::now(),
         }
     }
 
     /// Creates a new game and returns its uuid and a handle to acces it
-    pub fn create_game(&mut self) -> (Uuid, Arc<RwLock<GameState>>) {
+    ///
+    /// - seed: Seed of the random events of the game, drawn randomly if `None`
+    /// - options: Rules of the lobby
+    pub fn create_game(
+        &self,
+        seed: Option<u64>,
+        options: LobbyOptions,
+    ) -> (Uuid, Arc<RwLock<GameState>>) {
         let uid = Uuid::new_v4();
-        self.games.insert(uid, GameState::new(uid));
-        (uid, self.get_game_by_id(uid).unwrap())
+        let game = GameState::new(
+            uid,
+            seed.unwrap_or_else(rand::random),
+            options,
+            self.players.clone(),
+            self.scheduler.clone(),
+        );
+        self.games.insert(uid, game.clone());
+        (uid, game)
     }
 
-    pub async fn try_remove_game(&mut self, uid: Uuid) {
-        let entry = self.games.entry(uid);
+    /// Removes a game if there is no player left in it
+    pub async fn try_remove_game(&self, uid: Uuid) {
+        if let Some(game) = self.get_game_by_id(uid) {
+            let closed = {
+                let mut lock = game.write().await;
+                if lock.player_count() == 0 {
+                    lock.close();
+                }
+                lock.player_count() == 0
+            };
 
-        if let Entry::Occupied(e) = entry {
-            if e.get().read().await.player_count() == 0 {
-                e.remove_entry();
+            if closed {
+                self.forget_game(uid);
             }
         }
     }
 
+    /// Removes a game, regardless of its players
+    pub async fn remove_game(&self, uid: Uuid) -> Option<Arc<RwLock<GameState>>> {
+        let game = self.get_game_by_id(uid)?;
+        game.write().await.close();
+        self.forget_game(uid);
+        Some(game)
+    }
+
+    /// Removes a closed game from the maps
+    ///
+    /// Must not be called while holding the lock of the game, as the shard of the game may be held by a task waiting
+    /// for this lock.
+    fn forget_game(&self, uid: Uuid) {
+        self.games.remove(&uid);
+        self.players.remove_game(uid);
+        self.scheduler.cancel(uid);
+    }
+
     /// Returns the game in which the player is currently playing, if there is one
-    pub async fn get_game_by_player(
-        &self,
-        puuid: &String,
-    ) -> Option<(Uuid, Arc<RwLock<GameState>>)> {
-        for g in self.games.iter() {
-            if g.1.read().await.has_player(puuid) {
-                return Some((*g.0, g.1.clone()));
+    pub fn get_game_by_player(&self, name: &str) -> Option<(Uuid, Arc<RwLock<GameState>>)> {
+        let uid = self.players.get(name)?;
+        Some((uid, self.get_game_by_id(uid)?))
+    }
+
+    /// Returns the game with the corresponding uuid
+    pub fn get_game_by_id(&self, uuid: Uuid) -> Option<Arc<RwLock<GameState>>> {
+        self.games.get(&uuid).map(|g| g.clone())
+    }
+
+    /// Revokes all the tokens issued for a login session
+    pub fn revoke_session(&self, sid: Uuid) {
+        let now = get_current_timestamp();
+
+        self.revoked_sessions.retain(|_, exp| *exp > now);
+        self.revoked_sessions
+            .insert(sid, now + env_config().refresh_token_duration);
+    }
+
+    /// Returns whether the tokens of a login session have been revoked
+    pub fn is_revoked(&self, sid: &Uuid) -> bool {
+        self.revoked_sessions.contains_key(sid)
+    }
+
+    /// Marks a refresh token as exchanged, so that it cannot be used again
+    ///
+    /// Returns whether the token had not been exchanged yet
+    pub fn spend_refresh_token(&self, id: Uuid, exp: u64) -> bool {
+        let now = get_current_timestamp();
+
+        self.spent_refresh_tokens.retain(|_, exp| *exp > now);
+        match self.spent_refresh_tokens.entry(id) {
+            Entry::Occupied(_) => false,
+            Entry::Vacant(e) => {
+                e.insert(exp);
+                true
             }
         }
+    }
+
+    /// Checks that a session is still valid, and records the activity of its player
+    pub fn check_session(&self, session: &UserSession) -> Result<(), UserSessionError> {
+        if self.is_revoked(&session.sid) {
+            return Err(UserSessionError::Revoked);
+        }
 
-        None
+        match self.names.get_mut(&session.name) {
+            Some(mut reservation) if reservation.id == session.reservation => {
+                reservation.last_seen = Instant::now();
+                Ok(())
+            }
+            _ => Err(UserSessionError::NameReleased),
+        }
     }
 
-    /// Returns the game with the corresponding uuid
-    pub fn get_game_by_id(&self, uuid: Uuid) -> Option<Arc<RwLock<GameState>>> {
-        self.games.get(&uuid).cloned()
+    /// Claims a name, or proves its ownership if it is already reserved
+    ///
+    /// Returns the id of the reservation
+    ///
+    /// - name: Name to claim
+    /// - secret: Secret proving the ownership of the name
+    pub fn claim_name(&self, name: &str, secret: &str) -> Result<Uuid, Error> {
+        if secret.is_empty() {
+            return Err(Error::InvalidSecret);
+        }
+
+        let existing = self
+            .names
+            .get(name)
+            .filter(|r| !r.is_expired())
+            .map(|r| r.clone());
+
+        if let Some(reservation) = existing {
+            return if reservation.verify(secret)? {
+                Ok(reservation.id)
+            } else {
+                Err(Error::NameTaken)
+            };
+        }
+
+        let reservation = NameReservation::new(secret)?;
+        match self.names.entry(name.to_owned()) {
+            Entry::Occupied(e) if !e.get().is_expired() => return Err(Error::NameTaken),
+            Entry::Occupied(mut e) => {
+                e.insert(reservation.clone());
+            }
+            Entry::Vacant(e) => {
+                e.insert(reservation.clone());
+            }
+        }
+
+        // Messages queued for the previous owner of the name must not leak to the new one
+        self.messages.remove(name);
+
+        Ok(reservation.id)
     }
 
-    /// Get the proxy for a player, or creates it if missing
-    pub fn get_or_create_proxy(&self, puuid: &String) -> PlayerProxy {
-        let mut lock = self.messages.lock().unwrap();
-
-        if let std::collections::hash_map::Entry::Vacant(e) = lock.entry(puuid.clone()) {
-            let proxy = PlayerProxy::default();
-            e.insert(proxy.clone());
-            proxy
-        } else {
-            lock.get(puuid).unwrap().clone()
+    /// Removes the proxies of the players who are not in a game and have not been seen for PROXY_IDLE_TIMEOUT
+    ///
+    /// Returns the names of the removed proxies
+    pub fn remove_stale_proxies(&self) -> Vec<String> {
+        let timeout = Duration::from_secs(env_config().proxy_idle_timeout);
+        let is_stale = |name: &String| {
+            let seen = self
+                .names
+                .get(name)
+                .is_some_and(|r| r.last_seen.elapsed() < timeout);
+
+            !seen && self.players.get(name).is_none()
+        };
+
+        let names = self
+            .messages
+            .iter()
+            .map(|e| e.key().clone())
+            .collect::<Vec<_>>();
+
+        names
+            .into_iter()
+            .filter(|name| self.messages.remove_if(name, |n, _| is_stale(n)).is_some())
+            .collect()
+    }
+
+    /// Deletes the expired games and the stale proxies
+    pub async fn collect_garbage(&self) {
+        let games = self
+            .games
+            .iter()
+            .map(|e| (*e.key(), e.value().clone()))
+            .collect::<Vec<_>>();
+
+        let mut expired = Vec::new();
+        for (uid, game) in games {
+            if game.read().await.is_expired() {
+                expired.push(uid);
+            }
+        }
+
+        for uid in expired.iter() {
+            self.remove_game(*uid).await;
+        }
+
+        let proxies = self.remove_stale_proxies();
+
+        if !expired.is_empty() || !proxies.is_empty() {
+            tracing::info!(
+                "Collected {} games and {} proxies",
+                expired.len(),
+                proxies.len()
+            );
         }
     }
+
+    /// Background task periodically collecting the garbage, until the state is dropped
+    pub async fn janitor(state: Weak<Self>) {
+        loop {
+            tokio::time::sleep(JANITOR_INTERVAL).await;
+
+            let Some(state) = state.upgrade() else {
+                break;
+            };
+            state.collect_garbage().await;
+        }
+    }
+
+    /// Get the proxy for a player, or creates it if missing
+    pub fn get_or_create_proxy(&self, name: &str) -> PlayerProxy {
+        self.messages
+            .entry(name.to_owned())
+            .or_insert_with(|| PlayerProxy::new(name.to_owned()))
+            .clone()
+    }
 }
 
 impl Default for State {
     fn default() -> Self {
-        Self::new()
+        Self::new(Scheduler::default())
     }
 }
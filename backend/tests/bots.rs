@@ -0,0 +1,87 @@
+//! Lobbies completed with bots controlled by the server
+
+mod common;
+
+use common::Simulation;
+use serde_json::json;
+
+#[rocket::async_test]
+async fn bots_fill_the_lobby_and_vote() {
+    let mut sim = Simulation::with_players(3, &["ahri"]).await;
+
+    for strategy in ["omniscient", "omniscient", "omniscient", "random"] {
+        sim.post(0, "/api/game/bots", Some(json!({ "strategy": strategy })))
+            .await
+            .expect("add a bot");
+    }
+    let error = sim.post(0, "/api/game/bots", None).await.unwrap_err();
+    assert_eq!(error["error"], "MAX_PLAYER_REACHED");
+    assert_eq!(
+        sim.get(0, "/api/game").await["bot_names"]
+            .as_array()
+            .unwrap()
+            .len(),
+        4
+    );
+
+    sim.post(0, "/api/game/start", None)
+        .await
+        .expect("start the draft");
+    sim.set_clock(json!({ "mode": "manual" })).await;
+    sim.post(0, "/api/game/start", None)
+        .await
+        .expect("start the game");
+    sim.advance_to(20).await;
+    sim.post(0, "/api/game/end", None)
+        .await
+        .expect("end the game");
+
+    sim.post(0, "/api/game/votes", Some(json!({ "Bot 1": "impostor" })))
+        .await
+        .expect("vote");
+    sim.wait_for(0, |m| m["type"] == "votes_completed").await;
+
+    let game = sim.get(0, "/api/game").await;
+    assert_eq!(game["state"], "end");
+    let votes = game["votes"].as_object().unwrap();
+    assert_eq!(votes.len(), 5);
+    for bot in ["Bot 1", "Bot 2", "Bot 3"] {
+        for (name, role) in game["roles"].as_object().unwrap() {
+            if name != bot {
+                assert_eq!(&votes[bot][name], role, "vote of {bot} for {name}");
+            }
+        }
+    }
+}
+
+#[rocket::async_test]
+async fn bots_leave_with_the_last_player() {
+    let sim = Simulation::with_players(3, &["ahri", "braum"]).await;
+
+    let error = sim.post(1, "/api/game/bots", None).await.unwrap_err();
+    assert_eq!(error["error"], "UNAUTHORIZED");
+    let error = sim
+        .post(0, "/api/game/bots", Some(json!({ "strategy": "omnicient" })))
+        .await
+        .unwrap_err();
+    assert_eq!(error["error"], "INVALID_FORM");
+    let name = sim
+        .post(0, "/api/game/bots", None)
+        .await
+        .expect("add a bot");
+    assert_eq!(name, "Bot 1");
+    let (_, error) = sim.login("Bot 1").await;
+    assert_eq!(error["error"], "INVALID_NAME");
+
+    sim.post(0, "/api/game/quit", None)
+        .await
+        .expect("quit the lobby");
+    assert_eq!(sim.get(1, "/api/game").await["host"], "braum");
+    sim.post(1, "/api/game/quit", None)
+        .await
+        .expect("quit the lobby");
+
+    let path = format!("/api/game/{}/join", sim.uid);
+    let error = sim.post(0, &path, None).await.unwrap_err();
+    assert_eq!(error["error"], "NOT_FOUND");
+}
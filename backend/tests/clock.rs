@@ -0,0 +1,79 @@
+//! Game time followed from the live data of the players
+
+mod common;
+
+use std::time::Duration;
+
+use common::{Frame, Simulation};
+use serde_json::json;
+
+#[rocket::async_test]
+async fn live_clock_never_goes_back() {
+    let sim = Simulation::with_players(2, &["ahri", "braum"]).await;
+    sim.post(0, "/api/game/start", None)
+        .await
+        .expect("start the draft");
+    sim.set_clock(json!({ "mode": "live" })).await;
+    sim.post(0, "/api/game/start", None)
+        .await
+        .expect("start the game");
+
+    let frame = Frame::start(&sim);
+    sim.upload(0, &frame.then(100.0, []))
+        .await
+        .expect("upload live data");
+    assert_eq!(sim.admin_status().await["game_time"], 100);
+
+    // The client of the other player lags behind
+    sim.upload(1, &frame.then(90.0, []))
+        .await
+        .expect("upload live data");
+    assert_eq!(sim.admin_status().await["game_time"], 100);
+
+    sim.upload(1, &frame.then(105.0, []))
+        .await
+        .expect("upload live data");
+    assert_eq!(sim.admin_status().await["game_time"], 105);
+}
+
+#[rocket::async_test]
+async fn frozen_clients_pause_the_clock() {
+    let sim = Simulation::with_players(2, &["ahri", "braum"]).await;
+    sim.post(0, "/api/game/start", None)
+        .await
+        .expect("start the draft");
+    sim.set_clock(json!({ "mode": "live" })).await;
+    sim.post(0, "/api/game/start", None)
+        .await
+        .expect("start the game");
+
+    // Both clients froze at slightly different game times, and keep uploading them in turns
+    let frame = Frame::start(&sim);
+    let upload_frozen = || async {
+        sim.upload(0, &frame.then(100.0, []))
+            .await
+            .expect("upload live data");
+        sim.upload(1, &frame.then(99.6, []))
+            .await
+            .expect("upload live data");
+    };
+    upload_frozen().await;
+    tokio::time::sleep(Duration::from_millis(2100)).await;
+    upload_frozen().await;
+
+    let status = sim.admin_status().await;
+    assert_eq!(status["game_paused"], true);
+    let paused_at = status["game_time"].as_u64().unwrap();
+    assert!((100..=102).contains(&paused_at), "{paused_at}");
+
+    tokio::time::sleep(Duration::from_secs(1)).await;
+    upload_frozen().await;
+    assert_eq!(sim.admin_status().await["game_time"], paused_at);
+
+    sim.upload(0, &frame.then(105.0, []))
+        .await
+        .expect("upload live data");
+    let status = sim.admin_status().await;
+    assert_eq!(status["game_paused"], false);
+    assert_eq!(status["game_time"], 105);
+}
@@ -0,0 +1,566 @@
+//! In-process harness simulating full games
+//!
+//! The server runs with Rocket's local client, players are scripted through the same routes as the frontend, and the
+//! live data of the League game is replayed from frames built by [Frame].
+
+// Each test binary only uses a part of the harness
+#![allow(dead_code)]
+
+use std::{
+    collections::HashMap,
+    sync::Once,
+    time::{Duration, Instant},
+};
+
+use rocket::{
+    http::{ContentType, Header, Method, Status},
+    local::asynchronous::Client,
+};
+use serde_json::{json, Value};
+
+pub const ADMIN_TOKEN: &str = "simulation-admin";
+
+/// Longest wait for a message produced by the background tasks of the server
+const MESSAGE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Configures the server once for all the tests of the binary, before anything reads the environment
+pub fn configure() {
+    static CONFIGURE: Once = Once::new();
+
+    CONFIGURE.call_once(|| {
+        std::env::set_var("JWT_SECRET", "simulation-secret");
+        std::env::set_var("SECURE_COOKIES", "false");
+        std::env::set_var("ADMIN_TOKEN", ADMIN_TOKEN);
+        // Shorter than the refresh tokens, so that tests see it bound them
+        std::env::set_var("SESSION_MAX_DURATION", "3600");
+        std::env::remove_var("LOL_API_KEY");
+
+        backend::env::env_config();
+        backend::keyring::keyring();
+    });
+}
+
+pub struct Player {
+    pub name: String,
+    token: String,
+    /// Messages received so far, in order
+    pub inbox: Vec<Value>,
+}
+
+pub struct Simulation {
+    client: Client,
+    pub uid: String,
+    /// Players of the lobby, in the order in which they joined, the first one being the host
+    pub players: Vec<Player>,
+}
+
+impl Player {
+    /// Name of the summoner played by this player in the frames
+    pub fn summoner(&self) -> String {
+        format!("{}#sim", self.name)
+    }
+
+    /// Returns the received messages of the given type
+    pub fn messages(&self, kind: &str) -> Vec<&Value> {
+        self.inbox.iter().filter(|m| m["type"] == kind).collect()
+    }
+}
+
+impl Simulation {
+    /// Starts a server and fills a lobby with five players, in a game created with the given seed
+    pub async fn new(seed: u64) -> Self {
+        Self::with_players(seed, &["ahri", "braum", "caitlyn", "darius", "ekko"]).await
+    }
+
+    /// Starts a server and has the given players join a game created with the given seed, the first one hosting it
+    pub async fn with_players(seed: u64, names: &[&str]) -> Self {
+        Self::create(json!({ "seed": seed }), names).await
+    }
+
+    /// Starts a server and has the given players join a game played by both teams, created with the given seed
+    pub async fn versus(seed: u64, names: &[&str]) -> Self {
+        Self::create(json!({ "seed": seed, "mode": "versus" }), names).await
+    }
+
+    /// Starts a server and has the given players join a game created through the admin API with the given form
+    pub async fn create(form: Value, names: &[&str]) -> Self {
+        configure();
+
+        let client = Client::untracked(backend::rocket())
+            .await
+            .expect("valid rocket instance");
+
+        let mut sim = Self {
+            client,
+            uid: String::new(),
+            players: Vec::new(),
+        };
+
+        let (status, uid) = sim.admin_post("/api/admin/games", form).await;
+        assert_eq!(status, Status::Ok);
+        sim.uid = uid.as_str().unwrap().to_owned();
+
+        for &name in names {
+            let (status, body) = sim.login(name).await;
+            assert_eq!(status, Status::Ok, "login of {name}: {body}");
+
+            sim.players.push(Player {
+                name: name.to_owned(),
+                token: body["token"].as_str().unwrap().to_owned(),
+                inbox: Vec::new(),
+            });
+        }
+
+        for i in 0..sim.players.len() {
+            let path = format!("/api/game/{}/join", sim.uid);
+            sim.post(i, &path, None).await.expect("join the lobby");
+        }
+
+        sim
+    }
+
+    /// Logs in with the given name, without joining the lobby
+    pub async fn login(&self, name: &str) -> (Status, Value) {
+        let body = json!({ "name": name, "secret": "secret" });
+        self.request(Method::Post, "/api/login", None, Some(body))
+            .await
+    }
+
+    /// Sends a request with the given credentials and a JSON body or none, and parses the JSON body of the response
+    async fn request(
+        &self,
+        method: Method,
+        path: &str,
+        credentials: Option<Header<'static>>,
+        body: Option<Value>,
+    ) -> (Status, Value) {
+        let mut request = self.client.req(method, path);
+        if let Some(credentials) = credentials {
+            request = request.header(credentials);
+        }
+        if let Some(body) = body {
+            request = request.header(ContentType::JSON).body(body.to_string());
+        }
+
+        let response = request.dispatch().await;
+        let status = response.status();
+        let body = response
+            .into_string()
+            .await
+            .and_then(|b| serde_json::from_str(&b).ok())
+            .unwrap_or(Value::Null);
+
+        (status, body)
+    }
+
+    /// Sends a request as a player, returning the body of the response as an error unless it succeeded
+    async fn request_as(
+        &self,
+        player: usize,
+        method: Method,
+        path: &str,
+        body: Option<Value>,
+    ) -> Result<Value, Value> {
+        let token = &self.players[player].token;
+        let bearer = Header::new("Authorization", format!("Bearer {token}"));
+
+        let (status, body) = self.request(method, path, Some(bearer), body).await;
+        if status == Status::Ok {
+            Ok(body)
+        } else {
+            Err(body)
+        }
+    }
+
+    /// Sends a request as a player, with a JSON body or none
+    pub async fn post(
+        &self,
+        player: usize,
+        path: &str,
+        body: Option<Value>,
+    ) -> Result<Value, Value> {
+        self.request_as(player, Method::Post, path, body).await
+    }
+
+    /// Fetches a route as a player
+    pub async fn get(&self, player: usize, path: &str) -> Value {
+        self.try_get(player, path)
+            .await
+            .unwrap_or_else(|body| panic!("GET {path}: {body}"))
+    }
+
+    /// Fetches a route as a player, which may fail
+    pub async fn try_get(&self, player: usize, path: &str) -> Result<Value, Value> {
+        self.request_as(player, Method::Get, path, None).await
+    }
+
+    async fn admin_post(&self, path: &str, body: Value) -> (Status, Value) {
+        let admin = Header::new("X-Admin-Token", ADMIN_TOKEN);
+        self.request(Method::Post, path, Some(admin), Some(body))
+            .await
+    }
+
+    /// Returns the status of the game seen by the admin API
+    pub async fn admin_status(&self) -> Value {
+        let admin = Header::new("X-Admin-Token", ADMIN_TOKEN);
+        let (status, games) = self
+            .request(Method::Get, "/api/admin/games", Some(admin), None)
+            .await;
+        assert_eq!(status, Status::Ok, "list games: {games}");
+
+        games
+            .as_array()
+            .and_then(|games| games.iter().find(|g| g["uid"] == self.uid.as_str()))
+            .cloned()
+            .expect("game listed by the admin API")
+    }
+
+    /// Changes the clock of the game through the admin API
+    pub async fn set_clock(&self, body: Value) {
+        let path = format!("/api/admin/games/{}/clock", self.uid);
+        let (status, body) = self.admin_post(&path, body).await;
+        assert_eq!(status, Status::Ok, "set clock: {body}");
+    }
+
+    /// Moves the game to the given state through the admin API
+    pub async fn force_state(&self, state: &str) {
+        let path = format!("/api/admin/games/{}/state", self.uid);
+        let (status, body) = self.admin_post(&path, json!({ "state": state })).await;
+        assert_eq!(status, Status::Ok, "force state: {body}");
+    }
+
+    /// Jumps to the given game time, the clock of the game must be manual
+    pub async fn advance_to(&self, game_time: u64) {
+        self.set_clock(json!({ "game_time": game_time })).await;
+    }
+
+    /// Returns the index of the player having the given role
+    pub fn player_with_role(&self, role: &str) -> Option<usize> {
+        self.players
+            .iter()
+            .position(|p| p.messages("role").iter().any(|m| m["role"] == role))
+    }
+
+    /// Returns the role of each player, as received in their messages
+    pub fn roles(&self) -> HashMap<String, String> {
+        self.players
+            .iter()
+            .filter_map(|p| {
+                let role = p.messages("role").last()?["role"].as_str()?.to_owned();
+                Some((p.name.clone(), role))
+            })
+            .collect()
+    }
+
+    /// Fetches the pending messages of all the players
+    pub async fn fetch_messages(&mut self) {
+        for i in 0..self.players.len() {
+            let messages = self.get(i, "/api/updates").await;
+            self.players[i]
+                .inbox
+                .extend(messages.as_array().cloned().unwrap_or_default());
+        }
+    }
+
+    /// Waits until a player received a message matching the predicate, processed by the background tasks
+    pub async fn wait_for(&mut self, player: usize, predicate: impl Fn(&Value) -> bool) -> Value {
+        let start = Instant::now();
+
+        loop {
+            self.fetch_messages().await;
+            if let Some(message) = self.players[player].inbox.iter().find(|m| predicate(m)) {
+                return message.clone();
+            }
+
+            assert!(
+                start.elapsed() < MESSAGE_TIMEOUT,
+                "{} did not receive the expected message, got {:?}",
+                self.players[player].name,
+                self.players[player].inbox
+            );
+            tokio::time::sleep(Duration::from_millis(20)).await;
+        }
+    }
+
+    /// Uploads a frame of live data as the given player, who is the active player of the frame
+    pub async fn upload(&self, player: usize, frame: &Frame) -> Result<Value, Value> {
+        let data = frame.render(&self.players[player].summoner());
+        self.post(player, "/api/game/live", Some(data)).await
+    }
+}
+
+/// A summoner of the League game
+#[derive(Debug, Clone)]
+pub struct Summoner {
+    pub name: String,
+    pub champion: String,
+    /// ORDER or CHAOS
+    pub team: &'static str,
+    /// TOP, JUNGLE, MIDDLE, BOTTOM or UTILITY
+    pub position: &'static str,
+    pub kills: usize,
+    pub deaths: usize,
+    pub assists: usize,
+}
+
+impl Summoner {
+    fn new(name: String, team: &'static str, position: &'static str) -> Self {
+        Self {
+            champion: format!("Champion of {name}"),
+            name,
+            team,
+            position,
+            kills: 0,
+            deaths: 0,
+            assists: 0,
+        }
+    }
+}
+
+pub const LANES: [&str; 5] = ["TOP", "JUNGLE", "MIDDLE", "BOTTOM", "UTILITY"];
+
+/// State of the League game at a given game time, as reported by the live client data
+#[derive(Debug, Clone)]
+pub struct Frame {
+    pub game_time: f64,
+    pub summoners: Vec<Summoner>,
+    /// Events, as sent by the live client data (`EventName`, `EventID`, `EventTime`...)
+    pub events: Vec<Value>,
+}
+
+impl Frame {
+    /// Creates the first frame of a game, the players of the simulation being on the ORDER side in lane order
+    pub fn start(sim: &Simulation) -> Self {
+        let allies = sim.players.iter().map(Player::summoner);
+        let enemies = LANES
+            .iter()
+            .map(|lane| format!("enemy {}", lane.to_lowercase()));
+
+        let summoners = allies
+            .map(|name| (name, "ORDER"))
+            .chain(enemies.map(|name| (name, "CHAOS")))
+            .zip(LANES.iter().cycle())
+            .map(|((name, team), position)| Summoner::new(name, team, position))
+            .collect();
+
+        Self::with_summoners(summoners)
+    }
+
+    /// Creates the first frame of a game played by both teams, the players being on their side in lane order
+    ///
+    /// - teams: Side of each player, as found in the status of the game
+    pub fn versus(sim: &Simulation, teams: &Value) -> Self {
+        let summoners = ["ORDER", "CHAOS"]
+            .into_iter()
+            .flat_map(|team| {
+                let players = sim
+                    .players
+                    .iter()
+                    .filter(move |p| teams[&p.name] == team)
+                    .map(Player::summoner);
+                let others = LANES
+                    .iter()
+                    .map(move |lane| format!("{} {}", team.to_lowercase(), lane.to_lowercase()));
+
+                players
+                    .chain(others)
+                    .zip(LANES)
+                    .map(move |(name, position)| Summoner::new(name, team, position))
+            })
+            .collect();
+
+        Self::with_summoners(summoners)
+    }
+
+    fn with_summoners(summoners: Vec<Summoner>) -> Self {
+        Self {
+            game_time: 0.0,
+            summoners,
+            events: vec![event(0, 0.0, "GameStart", json!({}))],
+        }
+    }
+
+    /// Returns the next frame, at the given game time and with additional events
+    pub fn then(
+        &self,
+        game_time: f64,
+        events: impl IntoIterator<Item = (&'static str, Value)>,
+    ) -> Self {
+        let mut next = self.clone();
+        next.game_time = game_time;
+
+        for (name, fields) in events {
+            if name == "ChampionKill" {
+                next.record_kill(&fields);
+            }
+            let id = next.events.len();
+            next.events.push(event(id, game_time, name, fields));
+        }
+
+        next
+    }
+
+    /// Returns the summoner playing at the given position
+    pub fn summoner_at(&self, team: &str, position: &str) -> &Summoner {
+        self.summoners
+            .iter()
+            .find(|s| s.team == team && s.position == position)
+            .expect("summoner at this position")
+    }
+
+    fn record_kill(&mut self, fields: &Value) {
+        for summoner in self.summoners.iter_mut() {
+            if fields["KillerName"] == summoner.name.as_str() {
+                summoner.kills += 1;
+            }
+            if fields["VictimName"] == summoner.name.as_str() {
+                summoner.deaths += 1;
+            }
+        }
+    }
+
+    /// Renders the frame as the `allgamedata` of the given active player
+    pub fn render(&self, active: &str) -> Value {
+        json!({
+            "activePlayer": active_player(active),
+            "allPlayers": self.summoners.iter().map(player_data).collect::<Vec<_>>(),
+            "events": { "Events": self.events },
+            "gameData": {
+                "gameMode": "CLASSIC",
+                "gameTime": self.game_time,
+                "mapName": "Map11",
+                "mapNumber": 11,
+                "mapTerrain": "Default",
+            },
+        })
+    }
+}
+
+fn event(id: usize, time: f64, name: &str, fields: Value) -> Value {
+    let mut event = json!({ "EventID": id, "EventName": name, "EventTime": time });
+    if let (Some(event), Some(fields)) = (event.as_object_mut(), fields.as_object()) {
+        event.extend(fields.clone());
+    }
+    event
+}
+
+/// End of the game, for the team of the active player
+pub fn game_end(result: &str) -> (&'static str, Value) {
+    ("GameEnd", json!({ "Result": result }))
+}
+
+fn rune(id: u16) -> Value {
+    json!({
+        "id": id,
+        "displayName": "Rune",
+        "rawDescription": "rune",
+        "rawDisplayName": "rune",
+    })
+}
+
+fn summoner_spell() -> Value {
+    json!({
+        "displayName": "Flash",
+        "rawDescription": "flash",
+        "rawDisplayName": "flash",
+    })
+}
+
+fn player_data(summoner: &Summoner) -> Value {
+    json!({
+        "championName": summoner.champion,
+        "isBot": false,
+        "isDead": false,
+        "items": [],
+        "level": 1,
+        "position": summoner.position,
+        "rawChampionName": summoner.champion,
+        "respawnTimer": 0.0,
+        "runes": {
+            "keystone": rune(8005),
+            "primaryRuneTree": rune(8000),
+            "secondaryRuneTree": rune(8100),
+        },
+        "scores": {
+            "assists": summoner.assists,
+            "creepScore": 0,
+            "deaths": summoner.deaths,
+            "kills": summoner.kills,
+            "wardScore": 0.0,
+        },
+        "skinID": 0,
+        "summonerName": summoner.name,
+        "summonerSpells": {
+            "summonerSpellOne": summoner_spell(),
+            "summonerSpellTwo": summoner_spell(),
+        },
+        "team": summoner.team,
+    })
+}
+
+fn active_player(name: &str) -> Value {
+    let ability = |id: &str| {
+        json!({
+            "abilityLevel": 1,
+            "displayName": id,
+            "id": id,
+            "rawDescription": id,
+            "rawDisplayName": id,
+        })
+    };
+    let stats = [
+        "abilityPower",
+        "armor",
+        "armorPenetrationFlat",
+        "attackDamage",
+        "attackRange",
+        "attackSpeed",
+        "bonusArmorPenetrationPercent",
+        "bonusMagicPenetrationPercent",
+        "critChance",
+        "critDamage",
+        "currentHealth",
+        "healShieldPower",
+        "healthRegenRate",
+        "lifeSteal",
+        "magicLethality",
+        "magicPenetrationFlat",
+        "magicPenetrationPercent",
+        "magicResist",
+        "maxHealth",
+        "moveSpeed",
+        "omnivamp",
+        "physicalLethality",
+        "physicalVamp",
+        "resourceMax",
+        "resourceRegenRate",
+        "resourceValue",
+        "spellVamp",
+        "tenacity",
+    ]
+    .into_iter()
+    .map(|stat| (stat.to_owned(), json!(1.0)))
+    .chain([("resourceType".to_owned(), json!("MANA"))])
+    .collect::<serde_json::Map<_, _>>();
+
+    json!({
+        "abilities": {
+            "Passive": ability("passive"),
+            "Q": ability("q"),
+            "W": ability("w"),
+            "E": ability("e"),
+            "R": ability("r"),
+        },
+        "championStats": stats,
+        "currentGold": 500.0,
+        "fullRunes": {
+            "generalRunes": [rune(8005)],
+            "keystone": rune(8005),
+            "primaryRuneTree": rune(8000),
+            "secondaryRuneTree": rune(8100),
+            "statRunes": [{ "id": 5008, "rawDescription": "stat" }],
+        },
+        "level": 1,
+        "summonerName": name,
+    })
+}
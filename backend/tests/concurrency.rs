@@ -0,0 +1,59 @@
+//! Requests racing on the same game from several threads
+
+mod common;
+
+use std::{sync::Arc, time::Duration};
+
+use common::Simulation;
+use rocket::tokio::{runtime::Builder, task, time};
+
+/// Longest duration of a race, after which the server is considered deadlocked
+const RACE_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// Runs the given race on worker threads, failing instead of hanging if they deadlock
+fn race<F: std::future::Future<Output = ()> + Send + 'static>(race: F) {
+    let runtime = Builder::new_multi_thread()
+        .worker_threads(4)
+        .enable_all()
+        .build()
+        .expect("valid runtime");
+
+    let race = runtime.spawn(race);
+    let result = runtime.block_on(async { time::timeout(RACE_TIMEOUT, race).await });
+    // Blocked workers would never be joined
+    runtime.shutdown_background();
+
+    result.expect("deadlock").expect("race");
+}
+
+#[test]
+fn lobby_is_fetched_while_the_last_player_quits() {
+    race(async {
+        for seed in 0..4 {
+            let sim = Arc::new(Simulation::with_players(seed, &["ahri"]).await);
+            let path = format!("/api/game/{}", sim.uid);
+
+            let fetches = (0..4)
+                .map(|_| {
+                    let sim = sim.clone();
+                    let path = path.clone();
+                    task::spawn(async move {
+                        // Until the lobby is removed
+                        while sim.try_get(0, &path).await.is_ok() {}
+                    })
+                })
+                .collect::<Vec<_>>();
+
+            time::sleep(Duration::from_millis(10)).await;
+            sim.post(0, "/api/game/quit", None)
+                .await
+                .expect("quit the lobby");
+
+            for fetch in fetches {
+                fetch.await.expect("fetch the lobby");
+            }
+            let error = sim.try_get(0, &path).await.unwrap_err();
+            assert_eq!(error["error"], "NOT_FOUND");
+        }
+    });
+}
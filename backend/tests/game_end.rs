@@ -0,0 +1,29 @@
+//! End of the League game, reported by the live data of the players
+
+mod common;
+
+use common::{game_end, Frame, Simulation};
+
+#[rocket::async_test]
+async fn game_end_event_opens_the_votes() {
+    let sim = Simulation::with_players(3, &["ahri", "braum"]).await;
+    sim.post(0, "/api/game/start", None)
+        .await
+        .expect("start the draft");
+    sim.post(0, "/api/game/start", None)
+        .await
+        .expect("start the game");
+
+    let frame = Frame::start(&sim).then(1200.0, []);
+    sim.upload(1, &frame).await.expect("upload live data");
+    assert_eq!(sim.admin_status().await["state"], "in_game");
+
+    // Sent by the live client with its fields in PascalCase, as { "EventName": "GameEnd", "Result": "Loss" }
+    sim.upload(1, &frame.then(1500.0, [game_end("Loss")]))
+        .await
+        .expect("upload the end of the game");
+
+    let status = sim.admin_status().await;
+    assert_eq!(status["state"], "voting");
+    assert_eq!(status["game_result"], "loss");
+}
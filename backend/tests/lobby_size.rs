@@ -0,0 +1,91 @@
+//! Lobbies with less than five players, and the compositions of the full ones
+
+mod common;
+
+use std::collections::HashSet;
+
+use common::Simulation;
+use serde_json::json;
+
+const NAMES: [&str; 4] = ["ahri", "braum", "caitlyn", "darius"];
+
+#[rocket::async_test]
+async fn composition_scales_to_the_lobby() {
+    for count in 2..=NAMES.len() {
+        for seed in 0..2 {
+            let mut sim = Simulation::with_players(seed, &NAMES[..count]).await;
+            sim.post(0, "/api/game/start", None)
+                .await
+                .expect("start the draft");
+            sim.fetch_messages().await;
+
+            let roles = sim.roles();
+            assert_eq!(roles.len(), count);
+            let distinct = roles.values().collect::<HashSet<_>>();
+            assert_eq!(distinct.len(), count, "duplicated role: {roles:?}");
+            assert!(distinct.contains(&"super_hero".to_owned()));
+
+            let traitors = roles
+                .values()
+                .filter(|r| *r == "impostor" || *r == "crook")
+                .count();
+            assert_eq!(traitors, 1, "{roles:?}");
+        }
+    }
+}
+
+#[rocket::async_test]
+async fn five_players_keep_the_roles_of_older_seeds() {
+    let mut sim = Simulation::new(0).await;
+    sim.post(0, "/api/game/start", None)
+        .await
+        .expect("start the draft");
+    sim.fetch_messages().await;
+
+    // Roles drawn for this seed before the lobbies could be smaller
+    let expected = [
+        ("ahri", "droid"),
+        ("braum", "crook"),
+        ("caitlyn", "super_hero"),
+        ("darius", "two_face"),
+        ("ekko", "impostor"),
+    ];
+    let roles = sim.roles();
+    for (name, role) in expected {
+        assert_eq!(roles[name], role, "role of {name}");
+    }
+}
+
+#[rocket::async_test]
+async fn duo_plays_until_the_votes() {
+    let solo = Simulation::with_players(1, &["ahri"]).await;
+    let error = solo.post(0, "/api/game/start", None).await.unwrap_err();
+    assert_eq!(error["error"], "NOT_ENOUGH_PLAYERS");
+
+    let mut sim = Simulation::with_players(1, &["ahri", "braum"]).await;
+
+    sim.post(0, "/api/game/start", None)
+        .await
+        .expect("start the draft");
+    sim.set_clock(json!({ "mode": "manual" })).await;
+    sim.post(0, "/api/game/start", None)
+        .await
+        .expect("start the game");
+    sim.advance_to(20).await;
+    sim.post(0, "/api/game/end", None)
+        .await
+        .expect("end the game");
+
+    sim.post(0, "/api/game/votes", Some(json!({ "braum": "impostor" })))
+        .await
+        .expect("vote");
+    assert_eq!(sim.get(0, "/api/game").await["state"], "voting");
+    let error = sim.post(1, "/api/game/quit", None).await.unwrap_err();
+    assert_eq!(error["error"], "INCORRECT_STATE");
+    sim.post(1, "/api/game/votes", Some(json!({ "ahri": "crook" })))
+        .await
+        .expect("vote");
+
+    sim.wait_for(1, |m| m["type"] == "votes_completed").await;
+    assert_eq!(sim.get(0, "/api/game").await["state"], "end");
+}
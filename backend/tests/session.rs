@@ -0,0 +1,118 @@
+//! Sessions of clients authenticated by cookies
+
+mod common;
+
+use rocket::{
+    http::{ContentType, Header, Status},
+    local::asynchronous::Client,
+};
+use serde_json::json;
+
+#[rocket::async_test]
+async fn refresh_cookie_requires_the_csrf_token() {
+    common::configure();
+    let client = Client::tracked(backend::rocket())
+        .await
+        .expect("valid rocket instance");
+
+    let response = client
+        .post("/api/login")
+        .header(ContentType::JSON)
+        .body(json!({ "name": "ahri", "secret": "secret" }).to_string())
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+    assert!(response.cookies().get("access").is_some());
+    assert!(response.cookies().get("session").is_none());
+    let csrf = response.cookies().get("csrf").unwrap().value().to_owned();
+
+    let response = client.post("/api/refresh").dispatch().await;
+    assert_eq!(response.status(), Status::Forbidden);
+
+    let response = client
+        .post("/api/refresh")
+        .header(Header::new("X-CSRF-Token", csrf))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Ok);
+}
+
+#[rocket::async_test]
+async fn rejected_tokens_are_reported() {
+    common::configure();
+    let client = Client::untracked(backend::rocket())
+        .await
+        .expect("valid rocket instance");
+
+    let response = client
+        .get("/api/game")
+        .header(Header::new("Authorization", "Bearer expired"))
+        .dispatch()
+        .await;
+    assert_eq!(response.status(), Status::Unauthorized);
+    let body = response.into_string().await.unwrap();
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&body).unwrap()["error"],
+        "INVALID_TOKEN"
+    );
+}
+
+/// Exchanges a refresh token, returning the status and the body of the response
+async fn refresh(client: &Client, refresh_token: &str) -> (Status, serde_json::Value) {
+    let response = client
+        .post("/api/refresh")
+        .header(ContentType::JSON)
+        .body(json!({ "refresh_token": refresh_token }).to_string())
+        .dispatch()
+        .await;
+    let status = response.status();
+    let body = response.into_string().await.unwrap_or_default();
+    (status, serde_json::from_str(&body).unwrap_or_default())
+}
+
+#[rocket::async_test]
+async fn refresh_tokens_are_rotated() {
+    common::configure();
+    let client = Client::untracked(backend::rocket())
+        .await
+        .expect("valid rocket instance");
+
+    let response = client
+        .post("/api/login")
+        .header(ContentType::JSON)
+        .body(json!({ "name": "braum", "secret": "secret" }).to_string())
+        .dispatch()
+        .await;
+    let body = response.into_string().await.unwrap();
+    let login = serde_json::from_str::<serde_json::Value>(&body).unwrap();
+    let first = login["refresh_token"].as_str().unwrap();
+
+    let (status, tokens) = refresh(&client, first).await;
+    assert_eq!(status, Status::Ok);
+    let second = tokens["refresh_token"].as_str().unwrap();
+    assert_ne!(first, second);
+
+    let (status, error) = refresh(&client, first).await;
+    assert_eq!(status, Status::Unauthorized);
+    assert_eq!(error["error"], "INVALID_TOKEN");
+
+    let (status, tokens) = refresh(&client, second).await;
+    assert_eq!(status, Status::Ok);
+
+    // The refreshed tokens cannot outlive the login
+    let claims = |token: &serde_json::Value| {
+        backend::keyring::keyring()
+            .decode::<serde_json::Value>(token.as_str().unwrap())
+            .unwrap()
+            .claims
+    };
+    let login_claims = claims(&login["refresh_token"]);
+    let end = login_claims["auth_time"].as_u64().unwrap()
+        + backend::env::env_config().session_max_duration;
+    for token in [&tokens["token"], &tokens["refresh_token"]] {
+        let claims = claims(token);
+        assert_eq!(claims["auth_time"], login_claims["auth_time"]);
+        assert!(claims["exp"].as_u64().unwrap() <= end);
+    }
+    assert_eq!(claims(&tokens["refresh_token"])["exp"], end);
+}
@@ -0,0 +1,160 @@
+//! Full games played by five scripted players, from the lobby to the end of the votes
+
+mod common;
+
+use std::collections::{HashMap, HashSet};
+
+use common::{game_end, Frame, Simulation};
+use serde_json::{json, Value};
+
+const ROLES: [&str; 7] = [
+    "super_hero",
+    "impostor",
+    "crook",
+    "kamikaze",
+    "romeo",
+    "two_face",
+    "droid",
+];
+
+fn is_state(state: &str) -> impl Fn(&Value) -> bool + '_ {
+    move |m| m["type"] == "state" && m["state"]["state"] == state
+}
+
+/// Plays a whole game with the given seed, checking the messages received by every role
+///
+/// Returns the role of each player.
+async fn play(seed: u64) -> HashMap<String, String> {
+    let mut sim = Simulation::new(seed).await;
+
+    // Setup -> Draft: every player receives a role
+    sim.post(0, "/api/game/start", None)
+        .await
+        .expect("start the draft");
+    sim.fetch_messages().await;
+    for player in &sim.players {
+        assert_eq!(player.messages("role").len(), 1, "{}", player.name);
+    }
+    let roles = sim.roles();
+    for role in ["super_hero", "two_face", "droid"] {
+        assert!(
+            roles.values().any(|r| r == role),
+            "missing {role}: {roles:?}"
+        );
+    }
+
+    // Draft -> InGame, with a clock driven by the test
+    sim.set_clock(json!({ "mode": "manual" })).await;
+    sim.post(0, "/api/game/start", None)
+        .await
+        .expect("start the game");
+    assert_eq!(sim.get(0, "/api/game").await["state"], "in_game");
+
+    sim.advance_to(1).await;
+    let two_face = sim.player_with_role("two_face").unwrap();
+    sim.wait_for(two_face, |m| m["type"] == "two_face_state")
+        .await;
+
+    // Each player uploads their own data, binding them to their summoner
+    let frame = Frame::start(&sim).then(1.0, []);
+    for i in 0..sim.players.len() {
+        sim.upload(i, &frame).await.expect("upload live data");
+    }
+    let summoners = sim.get(0, "/api/game").await["summoners"].clone();
+    for player in &sim.players {
+        assert_eq!(summoners[&player.name]["summoner_name"], player.summoner());
+    }
+
+    if let Some(kamikaze) = sim.player_with_role("kamikaze") {
+        let rank = sim
+            .wait_for(kamikaze, |m| m["type"] == "kamikaze_rank")
+            .await;
+        assert_eq!(rank["deaths_rank"], 1);
+    }
+
+    if let Some(romeo) = sim.player_with_role("romeo") {
+        let juliette = sim.wait_for(romeo, |m| m["type"] == "juliette").await;
+        assert_ne!(juliette["juliette"], juliette["substitute"]);
+    }
+
+    // Missions and flips are all due well before this game time
+    sim.advance_to(3000).await;
+    let droid = sim.player_with_role("droid").unwrap();
+    let mission = sim.wait_for(droid, |m| m["type"] == "mission").await;
+    assert!(mission["mission"].is_string());
+    // The flips are processed in the same tick as the missions
+    assert!(sim.players[two_face].messages("two_face_state").len() >= 2);
+
+    // InGame -> Voting, as soon as the League game ends
+    let frame = frame.then(3000.0, [game_end("Win")]);
+    sim.upload(0, &frame)
+        .await
+        .expect("upload the end of the game");
+    for i in 0..sim.players.len() {
+        sim.wait_for(i, is_state("voting")).await;
+    }
+
+    // Voting -> End, once everyone voted
+    let names = sim
+        .players
+        .iter()
+        .map(|p| p.name.clone())
+        .collect::<Vec<_>>();
+    for i in 0..sim.players.len() {
+        let ballots = names
+            .iter()
+            .filter(|n| **n != names[i])
+            .map(|n| (n.clone(), json!("impostor")))
+            .collect::<serde_json::Map<_, _>>();
+        sim.post(i, "/api/game/votes", Some(Value::Object(ballots)))
+            .await
+            .expect("vote");
+    }
+    for i in 0..sim.players.len() {
+        sim.wait_for(i, |m| m["type"] == "votes_completed").await;
+    }
+
+    let game = sim.get(0, "/api/game").await;
+    assert_eq!(game["state"], "end");
+    assert_eq!(game["game_result"], "win");
+    for (name, role) in &roles {
+        assert_eq!(game["roles"][name], role.as_str());
+    }
+
+    roles
+}
+
+#[rocket::async_test]
+async fn every_role_plays_a_full_game() {
+    let mut played = HashSet::new();
+
+    for seed in 0..20 {
+        played.extend(play(seed).await.into_values());
+        if played.len() == ROLES.len() {
+            return;
+        }
+    }
+
+    panic!("some roles were never assigned: {played:?}");
+}
+
+#[rocket::async_test]
+async fn seed_reproduces_the_roles() {
+    let first = Simulation::new(42).await;
+    let second = Simulation::new(42).await;
+
+    for sim in [&first, &second] {
+        sim.post(0, "/api/game/start", None)
+            .await
+            .expect("start the draft");
+    }
+
+    let mut roles = Vec::new();
+    for mut sim in [first, second] {
+        sim.fetch_messages().await;
+        roles.push(sim.roles());
+    }
+
+    assert_eq!(roles[0].len(), 5);
+    assert_eq!(roles[0], roles[1]);
+}
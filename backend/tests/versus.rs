@@ -0,0 +1,150 @@
+//! Lobbies of ten players, playing both teams of the League game
+
+mod common;
+
+use std::collections::HashSet;
+
+use common::{game_end, Frame, Simulation};
+use serde_json::{json, Value};
+
+const NAMES: [&str; 10] = [
+    "ahri", "braum", "caitlyn", "darius", "ekko", "fiora", "garen", "hecarim", "irelia", "jinx",
+];
+
+#[rocket::async_test]
+async fn both_teams_play_and_vote() {
+    let mut sim = Simulation::versus(8, &NAMES).await;
+
+    // Players are split between both sides as they join
+    let game = sim.get(0, "/api/game").await;
+    assert_eq!(game["mode"], "versus");
+    let teams = game["teams"].clone();
+    let side = |name: &str| teams[name].as_str().unwrap().to_owned();
+    let order = NAMES.iter().filter(|n| side(n) == "ORDER").count();
+    assert_eq!(order, 5);
+
+    let other = if side("ahri") == "ORDER" {
+        "CHAOS"
+    } else {
+        "ORDER"
+    };
+    let error = sim
+        .post(0, "/api/game/team", Some(json!({ "team": other })))
+        .await
+        .unwrap_err();
+    assert_eq!(error["error"], "MAX_PLAYER_REACHED");
+
+    // Each team receives its own composition
+    sim.post(0, "/api/game/start", None)
+        .await
+        .expect("start the draft");
+    sim.fetch_messages().await;
+    let roles = sim.roles();
+    for team in ["ORDER", "CHAOS"] {
+        let team_roles = roles
+            .iter()
+            .filter(|(name, _)| side(name) == team)
+            .map(|(_, role)| role.as_str())
+            .collect::<HashSet<_>>();
+        assert_eq!(team_roles.len(), 5, "{team}: {roles:?}");
+        assert!(team_roles.contains("super_hero"));
+    }
+
+    sim.set_clock(json!({ "mode": "manual" })).await;
+    sim.post(0, "/api/game/start", None)
+        .await
+        .expect("start the game");
+
+    // Every player is bound to their summoner, on their side
+    let frame = Frame::versus(&sim, &teams).then(1.0, []);
+    for i in 0..sim.players.len() {
+        sim.upload(i, &frame).await.expect("upload live data");
+    }
+    let summoners = sim.get(0, "/api/game").await["summoners"].clone();
+    for player in &sim.players {
+        assert_eq!(summoners[&player.name]["summoner_name"], player.summoner());
+    }
+
+    let enemy = frame.summoner_at(other, "TOP").name.clone();
+    let error = sim
+        .post(
+            0,
+            "/api/game/bind",
+            Some(json!({ "player": "ahri", "summoner_name": enemy })),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(error["error"], "WRONG_TEAM");
+
+    // The game ends for both teams with a single upload
+    let loser = sim
+        .players
+        .iter()
+        .position(|p| side(&p.name) == other)
+        .unwrap();
+    sim.upload(loser, &frame.then(1000.0, [game_end("Loss")]))
+        .await
+        .expect("upload the end of the game");
+
+    // Ballots only name the other players of the team of the voter
+    let opponent = NAMES.iter().find(|n| side(n) == other).unwrap();
+    for guess in [opponent, "ahri", "zed"] {
+        let error = sim
+            .post(0, "/api/game/votes", Some(json!({ guess: "crook" })))
+            .await
+            .unwrap_err();
+        assert_eq!(error["error"], "INVALID_BALLOT", "guess for {guess}");
+    }
+
+    // Each team is notified once all its players voted
+    let mut voters = (0..sim.players.len()).collect::<Vec<_>>();
+    voters.sort_by_key(|i| side(&sim.players[*i].name) != side("ahri"));
+    for (count, i) in voters.into_iter().enumerate() {
+        let name = sim.players[i].name.clone();
+        let ballots = NAMES
+            .iter()
+            .filter(|n| **n != name && side(n) == side(&name))
+            .map(|n| (n.to_string(), json!("impostor")))
+            .collect::<serde_json::Map<_, _>>();
+
+        sim.post(i, "/api/game/votes", Some(Value::Object(ballots)))
+            .await
+            .expect("vote");
+
+        if count == 4 {
+            sim.wait_for(0, |m| m["type"] == "votes_completed").await;
+            assert_eq!(sim.get(0, "/api/game").await["state"], "voting");
+            let loser = &sim.players[loser];
+            assert!(loser.messages("votes_completed").is_empty());
+        }
+    }
+    sim.wait_for(loser, |m| m["type"] == "votes_completed")
+        .await;
+
+    let game = sim.get(0, "/api/game").await;
+    assert_eq!(game["state"], "end");
+    assert_eq!(game["winning_team"], side("ahri"));
+    assert!(game.get("game_result").is_none());
+    assert_eq!(game["votes"].as_object().unwrap().len(), 10);
+}
+
+#[rocket::async_test]
+async fn opponents_are_guessed_when_allowed() {
+    let form = json!({ "seed": 8, "mode": "versus", "cross_team_guesses": true });
+    let sim = Simulation::create(form, &NAMES).await;
+    sim.force_state("voting").await;
+
+    let game = sim.get(0, "/api/game").await;
+    assert_eq!(game["cross_team_guesses"], true);
+    let teams = game["teams"].clone();
+    let opponent = NAMES.iter().find(|n| teams[*n] != teams["ahri"]).unwrap();
+
+    sim.post(0, "/api/game/votes", Some(json!({ *opponent: "crook" })))
+        .await
+        .expect("guess the role of an opponent");
+    let error = sim
+        .post(1, "/api/game/votes", Some(json!({ "braum": "crook" })))
+        .await
+        .unwrap_err();
+    assert_eq!(error["error"], "INVALID_BALLOT");
+}
@@ -4,9 +4,31 @@ const API_URL = "https://al.lmermod.ch/api";
 
 export interface LoginResponse {
   token: string;
+  // Exchanged for a new pair of tokens once the access token expires
+  refresh_token: string;
 }
 
-export type GameState = { uid: string; player_names: string[] } & (
+export type Team = "ORDER" | "CHAOS";
+
+// Rules of a lobby, chosen when creating it
+export interface LobbyOptions {
+  // Whether the players are split between both teams of the League game
+  mode?: "single" | "versus";
+  // Whether the players of a versus lobby may guess the roles of their opponents
+  cross_team_guesses?: boolean;
+}
+
+export type GameState = {
+  uid: string;
+  player_names: string[];
+  // Players controlled by the server
+  bot_names: string[];
+  host: string | null;
+  mode: "single" | "versus";
+  cross_team_guesses: boolean;
+  // Side of each player, only filled in versus lobbies
+  teams: { [key: string]: Team };
+} & (
   | {
       state: "setup";
     }
@@ -55,25 +77,124 @@ function getSessionToken(): string | null {
   return new Cookies().get("session");
 }
 
-export async function login(name: string): Promise<LoginResponse | ApiError> {
-  let res = await fetch(`${API_URL}/login`, {
+function getRefreshToken(): string | null {
+  return new Cookies().get("refresh_token");
+}
+
+// Stores the tokens of the session, or removes them when logging out
+export function storeTokens(tokens: LoginResponse | null) {
+  const cookies = new Cookies();
+  if (tokens) {
+    cookies.set("session", tokens.token, { path: "/" });
+    cookies.set("refresh_token", tokens.refresh_token, { path: "/" });
+  } else {
+    cookies.remove("session", { path: "/" });
+    cookies.remove("refresh_token", { path: "/" });
+  }
+}
+
+// Refresh in progress, shared by the concurrent requests since each refresh token can only be used once
+let pendingRefresh: Promise<string | null> | null = null;
+
+// Exchanges the refresh token for a new pair of tokens, returning the new access token
+export function refreshTokens(): Promise<string | null> {
+  if (!pendingRefresh) {
+    pendingRefresh = exchangeRefreshToken().finally(() => {
+      pendingRefresh = null;
+    });
+  }
+  return pendingRefresh;
+}
+
+async function exchangeRefreshToken(): Promise<string | null> {
+  const refreshToken = getRefreshToken();
+  if (!refreshToken) {
+    return null;
+  }
+
+  let res = await fetch(`${API_URL}/refresh`, {
     method: "POST",
     headers: {
       "Content-Type": "application/json",
     },
-    body: JSON.stringify({ name: name }),
+    body: JSON.stringify({ refresh_token: refreshToken }),
   });
+  if (res.status != 200) {
+    return null;
+  }
 
-  return res.json();
+  const tokens = (await res.json()) as LoginResponse;
+  storeTokens(tokens);
+  return tokens.token;
 }
 
-export async function createGame(bearer?: string): Promise<string | null> {
-  let res = await fetch(`${API_URL}/game`, {
+// Sends a request authenticated by the access token, refreshing it once if it expired
+//
+// Requests made with an explicit bearer, from the server side, are not retried.
+async function authFetch(
+  path: string,
+  init: RequestInit,
+  bearer?: string
+): Promise<globalThis.Response> {
+  const send = (token: string | null) =>
+    fetch(`${API_URL}${path}`, {
+      ...init,
+      headers: { ...init.headers, Authorization: `Bearer ${token}` },
+    });
+
+  let res = await send(bearer || getSessionToken());
+  if (res.status == 401 && !bearer) {
+    const error = (await res
+      .clone()
+      .json()
+      .catch(() => null)) as ApiError | null;
+    if (error?.error == "INVALID_TOKEN") {
+      const token = await refreshTokens();
+      if (token) {
+        res = await send(token);
+      }
+    }
+  }
+  return res;
+}
+
+// Secret of this device, proving the ownership of the names it logged in with
+function getDeviceSecret(): string {
+  let secret = localStorage.getItem("device_secret");
+  if (!secret) {
+    secret = crypto.randomUUID();
+    localStorage.setItem("device_secret", secret);
+  }
+  return secret;
+}
+
+export async function login(name: string): Promise<LoginResponse | ApiError> {
+  let res = await fetch(`${API_URL}/login`, {
     method: "POST",
     headers: {
-      Authorization: `Bearer ${bearer || getSessionToken()}`,
+      "Content-Type": "application/json",
     },
+    body: JSON.stringify({ name: name, secret: getDeviceSecret() }),
   });
+
+  return res.json();
+}
+
+export async function createGame(
+  options?: LobbyOptions,
+  bearer?: string
+): Promise<string | null> {
+  let res = await authFetch(
+    `/game`,
+    {
+      method: "POST",
+      headers: {
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify(options || {}),
+    },
+    bearer
+  );
   return res.status == 200 ? res.json() : null;
 }
 
@@ -81,74 +202,122 @@ export async function getGame(
   uid: string,
   bearer?: string
 ): Promise<GameState | null> {
-  let res = await fetch(`${API_URL}/game/${uid}`, {
-    method: "GET",
-    headers: {
-      Authorization: `Bearer ${bearer || getSessionToken()}`,
+  let res = await authFetch(
+    `/game/${uid}`,
+    {
+      method: "GET",
     },
-  });
+    bearer
+  );
   return res.status == 200 ? res.json() : null;
 }
 
 export async function getCurrentGame(
   bearer?: string
 ): Promise<Response<GameState>> {
-  let res = await fetch(`${API_URL}/game`, {
-    method: "GET",
-    headers: {
-      Authorization: `Bearer ${bearer || getSessionToken()}`,
+  let res = await authFetch(
+    `/game`,
+    {
+      method: "GET",
     },
-  });
+    bearer
+  );
   return await convertResponse(res);
 }
 
 export async function joinGame(uid: string, bearer?: string) {
-  let res = await fetch(`${API_URL}/game/${uid}/join`, {
-    method: "POST",
-    headers: {
-      Authorization: `Bearer ${bearer || getSessionToken()}`,
+  let res = await authFetch(
+    `/game/${uid}/join`,
+    {
+      method: "POST",
     },
-  });
+    bearer
+  );
   return await convertResponse(res);
 }
 
 export async function startGame(bearer?: string) {
-  let res = await fetch(`${API_URL}/game/start`, {
-    method: "POST",
-    headers: {
-      Authorization: `Bearer ${bearer || getSessionToken()}`,
+  let res = await authFetch(
+    `/game/start`,
+    {
+      method: "POST",
     },
-  });
+    bearer
+  );
 }
 
 export async function endGame(bearer?: string) {
-  let res = await fetch(`${API_URL}/game/end`, {
-    method: "POST",
-    headers: {
-      Authorization: `Bearer ${bearer || getSessionToken()}`,
+  let res = await authFetch(
+    `/game/end`,
+    {
+      method: "POST",
     },
-  });
+    bearer
+  );
 }
 
 export async function quitGame(bearer?: string) {
-  await fetch(`${API_URL}/game/quit`, {
-    method: "POST",
-    headers: {
-      Authorization: `Bearer ${bearer || getSessionToken()}`,
+  await authFetch(
+    `/game/quit`,
+    {
+      method: "POST",
     },
-  });
+    bearer
+  );
+}
+
+// Moves a player to the given side of a versus lobby, the current player if none is given
+export async function setTeam(team: Team, player?: string, bearer?: string) {
+  let res = await authFetch(
+    `/game/team`,
+    {
+      method: "POST",
+      headers: {
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify({ team: team, player: player }),
+    },
+    bearer
+  );
+  return await convertResponse(res);
+}
+
+// Fills a free slot of the lobby with a bot, only allowed to the host
+export async function addBot(bearer?: string) {
+  let res = await authFetch(
+    `/game/bots`,
+    {
+      method: "POST",
+    },
+    bearer
+  );
+  return await convertResponse<string>(res);
+}
+
+export async function removeBot(name: string, bearer?: string) {
+  let res = await authFetch(
+    `/game/bots/${encodeURIComponent(name)}`,
+    {
+      method: "DELETE",
+    },
+    bearer
+  );
+  return await convertResponse(res);
 }
 
 export async function sendVotes(
   votes: { [key: string]: string },
   bearer?: string
 ) {
-  await fetch(`${API_URL}/game/votes`, {
-    method: "POST",
-    headers: {
-      Authorization: `Bearer ${bearer || getSessionToken()}`,
-      "Content-Type": "application/json",
+  await authFetch(
+    `/game/votes`,
+    {
+      method: "POST",
+      headers: {
+        "Content-Type": "application/json",
+      },
+      body: JSON.stringify(votes),
     },
-    body: JSON.stringify(votes),
-  });
+    bearer
+  );
 }
@@ -2,8 +2,8 @@ import localFont from "next/font/local";
 
 import "../styles/globals.css";
 import { useEffect, useState } from "react";
-import { useCookies } from "react-cookie";
 import getSessionJWT, { Session, decodeSession } from "@/session";
+import { LoginResponse, refreshTokens, storeTokens } from "@/api";
 import { useRouter } from "next/router";
 import { GetServerSidePropsContext, GetServerSidePropsResult } from "next";
 
@@ -12,22 +12,27 @@ const TITLE_FONT = localFont({
 });
 
 export default function App({ Component, pageProps }: any) {
-  var [cookies, setCookies, removeCookies] = useCookies(["session"]);
+  const router = useRouter();
   const [session, setSession] = useState(null as Session | null);
 
-  function setSessionToken(token: string) {
-    if (token) {
-      setCookies("session", token);
-      setSession(decodeSession(token));
-    } else {
-      removeCookies("session");
-      setSession(null);
-    }
+  function setSessionTokens(tokens: LoginResponse | null) {
+    storeTokens(tokens);
+    setSession(tokens ? decodeSession(tokens.token) : null);
   }
 
   useEffect(() => {
     var tmp = getSessionJWT();
     setSession(tmp);
+
+    // Pages rendered by the server with an expired token are rendered again once it is refreshed
+    if (tmp && tmp.exp * 1000 < Date.now()) {
+      refreshTokens().then((token) => {
+        if (token) {
+          setSession(decodeSession(token));
+          router.replace(router.asPath);
+        }
+      });
+    }
   }, []);
 
   return (
@@ -39,7 +44,7 @@ export default function App({ Component, pageProps }: any) {
       `}</style>
       <Component
         session={session}
-        setSessionToken={setSessionToken}
+        setSessionTokens={setSessionTokens}
         {...pageProps}
       />
     </main>
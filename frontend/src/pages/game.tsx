@@ -6,6 +6,10 @@ import {
   sendVotes,
   endGame,
   joinGame,
+  addBot,
+  removeBot,
+  setTeam,
+  Team,
 } from "@/api";
 import { promises as fs } from "fs";
 import { useRouter } from "next/router";
@@ -24,7 +28,21 @@ import path from "path";
 import getSessionJWT from "@/session";
 import { GetServerSidePropsContext, GetServerSidePropsResult } from "next";
 
-function PlayerInfo({ name }: { name?: string }) {
+// Smallest lobby which can be started, as enforced by the server
+const MIN_PLAYERS = 2;
+// Largest lobby, a whole League team, or largest team of a versus lobby
+const MAX_PLAYERS = 5;
+const TEAMS: Team[] = ["ORDER", "CHAOS"];
+const TEAM_NAMES = { ORDER: "Blue side", CHAOS: "Red side" };
+
+function PlayerInfo({
+  name,
+  onRemove,
+}: {
+  name?: string;
+  // Removes the player from the lobby, only given for the bots when hosting
+  onRemove?: () => void;
+}) {
   return (
     <p
       className={
@@ -39,6 +57,11 @@ function PlayerInfo({ name }: { name?: string }) {
       }}
     >
       {name}
+      {onRemove && (
+        <button className="ml-3 text-red-900" onClick={onRemove}>
+          ✕
+        </button>
+      )}
     </p>
   );
 }
@@ -84,6 +107,38 @@ export default function Game({
     }
   }
 
+  // Names of the players on the given side of a versus lobby
+  function teamNames(team: Team) {
+    return game.player_names.filter((n) => game.teams[n] == team);
+  }
+
+  function lobbyCapacity() {
+    return game.mode == "versus" ? 2 * MAX_PLAYERS : MAX_PLAYERS;
+  }
+
+  function canStart() {
+    return game.mode == "versus"
+      ? TEAMS.every((team) => teamNames(team).length >= MIN_PLAYERS)
+      : game.player_names.length >= MIN_PLAYERS;
+  }
+
+  // Players whose role is guessed by the current player, only their teammates unless the lobby allows more
+  function voteTargets() {
+    var session = getSessionJWT();
+    return game.player_names.filter(
+      (n) =>
+        n != session?.name &&
+        (game.mode != "versus" ||
+          game.cross_team_guesses ||
+          (session && game.teams[n] == game.teams[session.name]))
+    );
+  }
+
+  function isHost() {
+    var session = getSessionJWT();
+    return game && session && game.host == session.name;
+  }
+
   function hasVoted() {
     var session = getSessionJWT();
     return (
@@ -130,19 +185,22 @@ export default function Game({
   }
 
   function PlayerInfos() {
-    function generatePlayerInfos() {
-      if (game) {
-        game.player_names.sort();
-      }
+    function generatePlayerInfos(names: string[]) {
+      names.sort();
 
       var arr = [];
-      for (var i = 0; i < 5; ++i) {
+      for (var i = 0; i < MAX_PLAYERS; ++i) {
+        const name = names.length > i ? names[i] : undefined;
         arr.push(
           <PlayerInfo
             key={`player${i}`}
-            name={
-              game && game?.player_names.length > i
-                ? game.player_names[i]
+            name={name}
+            onRemove={
+              name &&
+              game.state == "setup" &&
+              isHost() &&
+              game.bot_names.indexOf(name) !== -1
+                ? () => removeBot(name).then(refreshGame)
                 : undefined
             }
           />
@@ -151,9 +209,37 @@ export default function Game({
       return arr;
     }
 
+    if (game && game.mode == "versus") {
+      var session = getSessionJWT();
+
+      return (
+        <div className="flex flex-row items-start justify-center gap-16">
+          {TEAMS.map((team) => (
+            <div
+              className="flex flex-col items-center justify-center gap-8"
+              key={team}
+            >
+              <p className="text-3xl">{TEAM_NAMES[team]}</p>
+              {generatePlayerInfos(teamNames(team))}
+              {game.state == "setup" &&
+                session &&
+                game.teams[session.name] != team && (
+                  <Button
+                    onClick={() => setTeam(team).then(refreshGame)}
+                    className="text-2xl py-2"
+                  >
+                    Join
+                  </Button>
+                )}
+            </div>
+          ))}
+        </div>
+      );
+    }
+
     return (
       <div className="flex flex-col items-center justify-center gap-8">
-        {generatePlayerInfos()}
+        {generatePlayerInfos(game ? game.player_names : [])}
       </div>
     );
   }
@@ -180,12 +266,21 @@ export default function Game({
               <div className="flex flex-col gap-5 justify-center items-center">
                 <PlayerInfos />
               </div>
-              {game.player_names.length == 5 ? (
+              {canStart() && (
                 <Button onClick={startGame} className="text-4xl py-3">
                   Start
                 </Button>
-              ) : (
+              )}
+              {game.player_names.length < lobbyCapacity() && (
                 <>
+                  {isHost() && (
+                    <Button
+                      onClick={() => addBot().then(refreshGame)}
+                      className="text-2xl py-2"
+                    >
+                      Add a bot
+                    </Button>
+                  )}
                   <div className="flex flex-col gap-5 justify-center items-center">
                     <p className="text-3xl">Invite your friends</p>
                     <p ref={inviteLinkRef} className="text-xl select-text">
@@ -252,54 +347,51 @@ export default function Game({
           );
 
         case "voting":
-          var session = getSessionJWT();
-
           return (
             <div className="flex flex-col items-center gap-10">
               <table className="border-separate border-spacing-x-8 border-spacing-y-4">
                 <thead>
                   <tr>
-                    {game.player_names
-                      .filter((p) => p != session?.name)
-                      .map((p) => (
-                        <th className="text-3xl" key={p}>
-                          {p}
-                        </th>
-                      ))}
+                    {voteTargets().map((p) => (
+                      <th className="text-3xl" key={p}>
+                        {p}
+                      </th>
+                    ))}
                   </tr>
                 </thead>
                 <tbody>
                   {Object.keys(data.roles).map((r) => (
                     <tr key={r}>
-                      {game.player_names
-                        .filter((n) => n != session?.name)
-                        .map((n) => (
-                          <td
-                            className={
-                              "text-2xl text-sky-400 p-3 rounded-lg text-center align-middle leading-10 bg-sky-800 " +
-                              (votes[n] == r ? "bg-slate-200" : "bg-sky-800")
+                      {voteTargets().map((n) => (
+                        <td
+                          className={
+                            "text-2xl text-sky-400 p-3 rounded-lg text-center align-middle leading-10 bg-sky-800 " +
+                            (votes[n] == r ? "bg-slate-200" : "bg-sky-800")
+                          }
+                          key={`${r}${n}`}
+                          onClick={() => {
+                            if (!hasVoted()) {
+                              var new_votes = JSON.parse(
+                                JSON.stringify(votes)
+                              );
+                              new_votes[n] = r;
+                              setVotes(new_votes);
                             }
-                            key={`${r}${n}`}
-                            onClick={() => {
-                              if (!hasVoted()) {
-                                var new_votes = JSON.parse(
-                                  JSON.stringify(votes)
-                                );
-                                new_votes[n] = r;
-                                setVotes(new_votes);
-                              }
-                            }}
-                          >
-                            {data.roles[r].name}
-                          </td>
-                        ))}
+                          }}
+                        >
+                          {data.roles[r].name}
+                        </td>
+                      ))}
                     </tr>
                   ))}
                 </tbody>
               </table>
               <Button
                 onClick={() => sendVotes(votes).then(refreshGame)}
-                disabled={Object.keys(votes).length != 4 || hasVoted()}
+                disabled={
+                  Object.keys(votes).length != voteTargets().length ||
+                  hasVoted()
+                }
                 className="text-3xl w-fit py-3"
               >
                 Submit
@@ -342,9 +434,9 @@ export default function Game({
                       {game.player_names.map((n2) => (
                         <td key={n2}>
                           <RoleInfo
-                            role={game.votes[n][n2]}
+                            role={game.votes[n]?.[n2]}
                             state={
-                              game.roles[n2] == game.votes[n][n2]
+                              game.roles[n2] == game.votes[n]?.[n2]
                                 ? "correct"
                                 : "wrong"
                             }
@@ -3,7 +3,7 @@ import { Button, Href } from "@/components/inputs";
 import { GetServerSidePropsContext, GetServerSidePropsResult } from "next";
 import { useRouter } from "next/router";
 
-export default function Home({ session, setSessionToken }: any) {
+export default function Home({ session, setSessionTokens }: any) {
   const router = useRouter();
 
   return (
@@ -28,7 +28,19 @@ export default function Home({ session, setSessionToken }: any) {
         <Button
           className="w-72 py-3"
           onClick={() => {
-            setSessionToken(null);
+            createGame({ mode: "versus" }).then((uid) => {
+              if (uid) {
+                router.push(`/game`);
+              }
+            });
+          }}
+        >
+          <p className="text-4xl">Create versus</p>
+        </Button>
+        <Button
+          className="w-72 py-3"
+          onClick={() => {
+            setSessionTokens(null);
             router.reload();
           }}
         >
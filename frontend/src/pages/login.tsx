@@ -3,7 +3,7 @@ import { Button } from "@/components/inputs";
 import { useRouter } from "next/router";
 import { useEffect, useRef, useState } from "react";
 
-export default function Login({ session, setSessionToken }: any) {
+export default function Login({ session, setSessionTokens }: any) {
   const [name, setName] = useState("");
   const [error, setError] = useState(null as ApiError | null);
   const [errorInfo, setErrorInfo] = useState(null as string | null);
@@ -13,7 +13,7 @@ export default function Login({ session, setSessionToken }: any) {
   function _login() {
     login(name).then((res) => {
       if ("token" in res) {
-        setSessionToken(res.token);
+        setSessionTokens(res);
       } else if ("error" in res) {
         setError(res);
       }
@@ -30,7 +30,13 @@ export default function Login({ session, setSessionToken }: any) {
     if (error) {
       switch (error.error) {
         case "INVALID_NAME":
-          setErrorInfo("Must be between 1 and 15 characters");
+          setErrorInfo(
+            'Must be between 1 and 15 characters, not starting with "Bot "'
+          );
+          break;
+
+        case "NAME_TAKEN":
+          setErrorInfo("This name is already used by someone else");
           break;
 
         default:
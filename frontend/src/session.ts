@@ -4,6 +4,8 @@ import { Cookies } from "react-cookie";
 export interface Session {
   name: string;
   summoner_name: string | null;
+  // Expiry of the access token, in epoch seconds
+  exp: number;
 }
 
 function getSessionToken(): string | null {